@@ -4,7 +4,7 @@ use proc_macro::{TokenStream, Diagnostic};
 use quote::quote;
 use quote::spanned::Spanned;
 use syn::parse::Parse;
-use syn::{braced, Ident, LitStr};
+use syn::{braced, bracketed, Ident, LitStr};
 use syn::{parse_macro_input, Token};
 
 #[proc_macro]
@@ -27,13 +27,36 @@ fn console_command_base(tokens: TokenStream, internal: bool) -> TokenStream {
     let crate_name = if internal { "crate" } else { "k9" };
 
     for f in pi.fields {
-        if f.optional{
+        if f.list {
+            // lists are never None, a missing optional list is empty
             match_str += format!(
                 r#"
                 let {0}: {3} = if let Some(x) = args.get("{0}") {{
                     match x {{
                         {1},
-                        _ => return Err("'{0}' was not a valid {2:?}".to_owned()),
+                        _ => return Err("'{0}' was not a valid {2}".to_owned()),
+                    }}
+                }} else {{
+                    {4}
+                }};
+                "#,
+                f.name,
+                match_callback_arg_value(&f, crate_name),
+                type_label(&f),
+                match_callback_arg_type_annotation(&f),
+                if f.optional {
+                    "Vec::new()".to_owned()
+                } else {
+                    format!(r#"return Err("missing variable '{}'".to_owned());"#, f.name)
+                },
+            ).as_str();
+        } else if f.optional{
+            match_str += format!(
+                r#"
+                let {0}: {3} = if let Some(x) = args.get("{0}") {{
+                    match x {{
+                        {1},
+                        _ => return Err("'{0}' was not a valid {2}".to_owned()),
                     }}
                 }} else {{
                     None
@@ -41,16 +64,26 @@ fn console_command_base(tokens: TokenStream, internal: bool) -> TokenStream {
                 "#,
                 f.name,
                 match_callback_arg_value(&f, crate_name),
-                f.ty,
+                type_label(&f),
                 match_callback_arg_type_annotation(&f),
             ).as_str();
+
+            // a default turns the binding back into the bare type
+            if let Some(default) = &f.default {
+                match_str += format!(
+                    "let {0}: {1} = {0}.unwrap_or({2});",
+                    f.name,
+                    match_callback_arg_type_name(&f),
+                    default,
+                ).as_str();
+            }
         } else {
             match_str += format!(
                 r#"
                 let {0}: {3} = if let Some(x) = args.get("{0}") {{
                     match x {{
                         {1},
-                        _ => return Err("'{0}' was not a valid {2:?}".to_owned()),
+                        _ => return Err("'{0}' was not a valid {2}".to_owned()),
                     }}
                 }} else {{
                     return Err("missing variable '{0}'".to_owned());
@@ -58,7 +91,7 @@ fn console_command_base(tokens: TokenStream, internal: bool) -> TokenStream {
                 "#,
                 f.name,
                 match_callback_arg_value(&f, crate_name),
-                f.ty,
+                type_label(&f),
                 match_callback_arg_type_annotation(&f),
             )
             .as_str();
@@ -70,14 +103,40 @@ fn console_command_base(tokens: TokenStream, internal: bool) -> TokenStream {
                 name: "{0}".to_owned(),
                 cba_type: {1},
                 optional: {2},
+                description: {3},
             }});
             "#,
             f.name,
             match_callback_arg_type_core(&f, crate_name),
             f.optional,
+            match &f.description {
+                // debug formatting gives an escaped string literal
+                Some(x) => format!("Some({x:?}.to_owned())"),
+                None => "None".to_owned(),
+            },
         )
         .as_str();
 
+        // the bounds check runs after any default has been applied
+        if let Some(range) = &f.range {
+            let check = format!(
+                r#"if !({range}).contains(x) {{ return Err(format!("'{0}' must be in {range}, got {{}}", x)); }}"#,
+                f.name,
+            );
+            match_str += bind_check(&f, &check).as_str();
+        }
+
+        if let ParameterType::OneOf(choices) = &f.ty {
+            let list = choices.join(", ");
+            let check = format!(
+                r#"if ![{0}].contains(&x.as_str()) {{ return Err(format!("'{1}' must be one of [{2}], got '{{}}'", x)); }}"#,
+                choices.iter().map(|x| format!("{x:?}")).collect::<Vec<_>>().join(", "),
+                f.name,
+                list.escape_debug().to_string().replace('{', "{{").replace('}', "}}"),
+            );
+            match_str += bind_check(&f, &check).as_str();
+        }
+
         param_names += format!("{}, ", f.name).as_str();
     }
 
@@ -101,41 +160,83 @@ fn console_command_base(tokens: TokenStream, internal: bool) -> TokenStream {
             
             let mut args = Vec::new();
             {args_str}
-            {crate_name}::debug_ui::ConsoleCommand::new(cb, args, "{1}".to_owned())
+            {crate_name}::debug_ui::ConsoleCommand::with_aliases(cb, args, "{1}".to_owned(), vec![{2}])
         }}
         "#,
         inner_cb,
         pi.description,
+        pi.aliases.iter().map(|x| format!("{x:?}.to_owned()")).collect::<Vec<_>>().join(", "),
     );
     //println!("{output_str}");
     output_str.parse().unwrap()
 }
 
+// the type as named in mismatch errors, choices are left out since they'd need escaping
+fn type_label(field: &ParameterParseInfo) -> String {
+    let label = match &field.ty {
+        ParameterType::OneOf(_) => "OneOf".to_owned(),
+        ty => format!("{ty:?}"),
+    };
+    if field.list {
+        label + "[]"
+    } else {
+        label
+    }
+}
+
+// runs check with x bound to a reference to the parsed value, skipped for a missing optional.
+// lists run it for every element.
+fn bind_check(field: &ParameterParseInfo, check: &str) -> String {
+    if field.list {
+        format!("for x in {}.iter() {{ {check} }}", field.name)
+    } else if field.optional && field.default.is_none() {
+        format!("if let Some(x) = &{} {{ {check} }}", field.name)
+    } else {
+        format!("{{ let x = &{}; {check} }}", field.name)
+    }
+}
+
 fn match_callback_arg_type_core(field: &ParameterParseInfo, crate_name: &str) -> String {
     let core = format!("{crate_name}::debug_ui::console::CallbackArgumentType::");
-    match &field.ty {
+    let element = match &field.ty {
         &ParameterType::F32 => core + "Float32",
         &ParameterType::F64 => core + "Float64",
         &ParameterType::I32 => core + "Int32",
         &ParameterType::I64 => core + "Int64",
-        &ParameterType::String => core + "String",
+        &ParameterType::U32 => core + "UInt32",
+        &ParameterType::U64 => core + "UInt64",
+        &ParameterType::USize => core + "USize",
+        &ParameterType::String | &ParameterType::OneOf(_) => core + "String",
         &ParameterType::Bool => core + "Bool",
         &ParameterType::Flag => core + "Flag",
+    };
+    if field.list {
+        format!("{crate_name}::debug_ui::console::CallbackArgumentType::List(Box::new({element}))")
+    } else {
+        element
     }
 }
 
-fn match_callback_arg_type_annotation(field: &ParameterParseInfo) -> String {
-    let core = match field.ty {
+fn match_callback_arg_type_name(field: &ParameterParseInfo) -> &'static str {
+    match field.ty {
         ParameterType::Bool => "bool",
         ParameterType::F32 => "f32",
         ParameterType::F64 => "f64",
         ParameterType::Flag => "bool",
         ParameterType::I32 => "i32",
         ParameterType::I64 => "i64",
-        ParameterType::String => "String",
-    };
+        ParameterType::U32 => "u32",
+        ParameterType::U64 => "u64",
+        ParameterType::USize => "usize",
+        ParameterType::String | ParameterType::OneOf(_) => "String",
+    }
+}
 
-    if field.optional {
+fn match_callback_arg_type_annotation(field: &ParameterParseInfo) -> String {
+    let core = match_callback_arg_type_name(field);
+    if field.list {
+        format!("Vec<{core}>")
+    } else if field.optional {
         format!("Option<{core}>")
     } else {
         core.to_owned()
@@ -144,25 +245,56 @@ fn match_callback_arg_type_annotation(field: &ParameterParseInfo) -> String {
 
 fn match_callback_arg_value(field: &ParameterParseInfo, crate_name: &str) -> String {
     let core = format!("{crate_name}::debug_ui::console::CallbackArgumentValue::");
-    let value = if field.optional {
-        "Some(*x)"
-    } else {
-        "*x"
+    // strings aren't Copy, so they're cloned out of the args map
+    let value = match (field.optional && !field.list, &field.ty) {
+        (true, ParameterType::String | ParameterType::OneOf(_)) => "Some(x.clone())",
+        (false, ParameterType::String | ParameterType::OneOf(_)) => "x.clone()",
+        (true, _) => "Some(*x)",
+        (false, _) => "*x",
     };
 
-    match field.ty {
+    // list elements are matched one at a time into a Vec, a mismatched element fails the whole list
+    let value = if field.list {
+        format!("list.push({value})")
+    } else {
+        value.to_owned()
+    };
+    let arm = match field.ty {
         ParameterType::F32 => core + format!("Float32(x) => {value}").as_str(),
         ParameterType::F64 => core + format!("Float64(x) => {value}").as_str(),
         ParameterType::I32 => core + format!("Int32(x) => {value}").as_str(),
         ParameterType::I64 => core + format!("Int64(x) => {value}").as_str(),
-        ParameterType::String => core + format!("String(x) => {value}").as_str(),
+        ParameterType::U32 => core + format!("UInt32(x) => {value}").as_str(),
+        ParameterType::U64 => core + format!("UInt64(x) => {value}").as_str(),
+        ParameterType::USize => core + format!("USize(x) => {value}").as_str(),
+        ParameterType::String | ParameterType::OneOf(_) => core + format!("String(x) => {value}").as_str(),
         ParameterType::Bool => core + format!("Bool(x) => {value}").as_str(),
         ParameterType::Flag => core + format!("Flag(x) => {value}").as_str(),
+    };
+
+    if field.list {
+        format!(
+            r#"{crate_name}::debug_ui::console::CallbackArgumentValue::List(x) => {{
+                let mut list = Vec::new();
+                for x in x {{
+                    match x {{
+                        {arm},
+                        _ => return Err("'{0}' was not a valid {1}".to_owned()),
+                    }}
+                }}
+                list
+            }}"#,
+            field.name,
+            type_label(field),
+        )
+    } else {
+        arm
     }
 }
 
 struct ConsoleCommandParseInfo {
     description: String,
+    aliases: Vec<String>,
     fields: Vec<ParameterParseInfo>,
     callback: syn::Expr,
 }
@@ -173,6 +305,19 @@ impl Parse for ConsoleCommandParseInfo {
 
         let _ = input.parse::<Token![,]>()?;
 
+        // optional alias list between the description and the parameters, e.g. `aliases["q"],`
+        let mut aliases = Vec::new();
+        if input.parse::<kw::aliases>().is_ok() {
+            let list;
+            let _ = bracketed!(list in input);
+            aliases = list
+                .parse_terminated(<LitStr as Parse>::parse, Token![,])?
+                .into_iter()
+                .map(|x| x.value())
+                .collect();
+            let _ = input.parse::<Token![,]>()?;
+        }
+
         let fields;
         let _ = braced!(fields in input);
         let fields: Vec<ParameterParseInfo> = fields
@@ -185,7 +330,7 @@ impl Parse for ConsoleCommandParseInfo {
 
         let callback = input.parse::<syn::Expr>()?;
 
-        Ok(Self { description, fields, callback })
+        Ok(Self { description, aliases, fields, callback })
     }
 }
 
@@ -194,6 +339,11 @@ struct ParameterParseInfo {
     name: String,
     ty: ParameterType,
     optional: bool,
+    description: Option<String>,
+    default: Option<String>,
+    range: Option<String>,
+    // `T[]`, collects any number of values into a Vec<T>
+    list: bool,
 }
 #[derive(Debug)]
 enum ParameterType {
@@ -201,7 +351,12 @@ enum ParameterType {
     F64,
     I32,
     I64,
+    U32,
+    U64,
+    USize,
     String,
+    // a string restricted to the listed choices
+    OneOf(Vec<String>),
     Bool,
     Flag,
 }
@@ -211,9 +366,14 @@ mod kw {
     custom_keyword!(f64);
     custom_keyword!(i32);
     custom_keyword!(i64);
+    custom_keyword!(u32);
+    custom_keyword!(u64);
+    custom_keyword!(usize);
     custom_keyword!(String);
     custom_keyword!(bool);
     custom_keyword!(Flag);
+    custom_keyword!(one_of);
+    custom_keyword!(aliases);
     custom_keyword!(opt);
 }
 impl Parse for ParameterParseInfo {
@@ -229,10 +389,29 @@ impl Parse for ParameterParseInfo {
             ParameterType::I32
         } else if input.parse::<kw::i64>().is_ok() {
             ParameterType::I64
+        } else if input.parse::<kw::u32>().is_ok() {
+            ParameterType::U32
+        } else if input.parse::<kw::u64>().is_ok() {
+            ParameterType::U64
+        } else if input.parse::<kw::usize>().is_ok() {
+            ParameterType::USize
         } else if input.parse::<kw::bool>().is_ok() {
             ParameterType::Bool
         } else if input.parse::<kw::String>().is_ok() {
             ParameterType::String
+        } else if input.parse::<kw::one_of>().is_ok() {
+            let choices;
+            let _ = bracketed!(choices in input);
+            let choices: Vec<String> = choices
+                .parse_terminated(<LitStr as Parse>::parse, Token![,])?
+                .into_iter()
+                .map(|x| x.value())
+                .collect();
+            if choices.is_empty() {
+                return Err(syn::Error::new(colon.span, "one_of needs at least one choice"));
+            }
+
+            ParameterType::OneOf(choices)
         } else if input.parse::<kw::Flag>().is_ok() {
             if let Ok(opt) = optional {
                 Diagnostic::spanned(opt.span.unwrap(), proc_macro::Level::Warning, "flag marked optional, flags are always considered optional.").emit();
@@ -255,7 +434,69 @@ impl Parse for ParameterParseInfo {
             return Err(syn::Error::new(span, msg));
         };
 
+        // any type but a flag can be made a list, e.g. `ids: i32[]`
+        let list = if input.peek(syn::token::Bracket) {
+            let empty;
+            let brackets = bracketed!(empty in input);
+            if !empty.is_empty() {
+                return Err(empty.error("expected `[]`"));
+            }
+            if matches!(ty, ParameterType::Flag) {
+                return Err(syn::Error::new(brackets.span.join(), "flags can't be lists"));
+            }
+            true
+        } else {
+            false
+        };
+
+        // optional parameters can give a default after the type, e.g. `opt count: i32 = 4`
+        let default = if let Ok(eq) = input.parse::<Token![=]>() {
+            let lit = input.parse::<syn::Lit>()?;
+            if optional.is_err() || matches!(ty, ParameterType::Flag) {
+                return Err(syn::Error::new(eq.span, "only optional, non-flag parameters can have a default"));
+            }
+            if list {
+                return Err(syn::Error::new(eq.span, "list parameters can't have a default, a missing optional list is empty"));
+            }
+
+            // string literals need owning to match the String binding
+            let lit = quote! { #lit }.to_string();
+            Some(match ty {
+                ParameterType::String | ParameterType::OneOf(_) => format!("{lit}.to_owned()"),
+                _ => lit,
+            })
+        } else {
+            None
+        };
+
+        // numeric parameters can be constrained to a range, e.g. `speed: f32 in 0.0..=10.0`
+        let range = if let Ok(in_token) = input.parse::<Token![in]>() {
+            if !matches!(ty, ParameterType::F32 | ParameterType::F64 | ParameterType::I32 | ParameterType::I64 | ParameterType::U32 | ParameterType::U64 | ParameterType::USize) {
+                return Err(syn::Error::new(in_token.span, "only numeric parameters can have a range"));
+            }
+
+            // bounds are literals rather than expressions so a following `=` or description isn't swallowed
+            let start = if input.peek(Token![..]) { None } else { Some(input.parse::<syn::Lit>()?) };
+            let limits = input.parse::<syn::RangeLimits>()?;
+            let end = if input.peek(syn::Lit) || input.peek(Token![-]) { Some(input.parse::<syn::Lit>()?) } else { None };
+            if start.is_none() && end.is_none() {
+                return Err(syn::Error::new(in_token.span, "range needs at least one bound"));
+            }
+
+            let range = quote! { #start #limits #end }.to_string();
+            Some(range.split_whitespace().collect::<String>())
+        } else {
+            None
+        };
+
+        // an optional description follows the type (and default), e.g. `x: f32 "the x coordinate"`
+        let description = if input.peek(LitStr) {
+            Some(input.parse::<LitStr>()?.value())
+        } else {
+            None
+        };
+
         let optional = optional.is_ok();
-        Ok(Self { name, ty, optional })
+        Ok(Self { name, ty, optional, description, default, range, list })
     }
 }
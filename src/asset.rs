@@ -0,0 +1,24 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::RwLock,
+};
+
+// set once by process::run from CreationArgs::asset_root, None resolves against the working directory
+static ASSET_ROOT: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+pub(crate) fn set_asset_root(root: Option<PathBuf>) {
+    *ASSET_ROOT.write().unwrap() = root;
+}
+
+// joins a relative asset path onto the asset root, absolute paths are returned untouched
+pub fn resolve_asset(path: impl AsRef<Path>) -> PathBuf {
+    let path = path.as_ref();
+    if path.is_absolute() {
+        return path.to_path_buf();
+    }
+
+    match &*ASSET_ROOT.read().unwrap() {
+        Some(root) => root.join(path),
+        None => path.to_path_buf(),
+    }
+}
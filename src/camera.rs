@@ -3,6 +3,7 @@ use std::f32::consts::PI;
 pub struct ScreenCamera {
     aspect_ratio: f32,
     fov: Angle,
+    position: glam::Vec3,
     view_proj_matrix: glam::Mat4,
     z_near: f32,
     z_far: f32,
@@ -12,6 +13,7 @@ impl ScreenCamera {
         let mut rval = Self {
             aspect_ratio,
             fov,
+            position: glam::Vec3::ZERO,
             view_proj_matrix: glam::Mat4::IDENTITY,
             z_near: near_far.0,
             z_far: near_far.1,
@@ -67,9 +69,44 @@ impl ScreenCamera {
         self.compute_view_proj_matrix();
     }
 
+    // the point the camera is focused on, the camera sits working_distance in front of it
+    pub fn position(&self) -> glam::Vec3 {
+        self.position
+    }
+    pub fn set_position(&mut self, position: glam::Vec3) {
+        self.position = position;
+        self.compute_view_proj_matrix();
+    }
+
+    // moves towards target by at most max_delta units, won't overshoot
+    pub fn move_towards(&mut self, target_pos: glam::Vec3, max_delta: f32) {
+        let to_target = target_pos - self.position;
+        let distance = to_target.length();
+        if distance <= max_delta || distance <= f32::EPSILON {
+            self.set_position(target_pos);
+        } else {
+            self.set_position(self.position + to_target / distance * max_delta);
+        }
+    }
+
+    // exponential ease towards target, each call covers 1 - exp(-factor * dt) of the remaining
+    // distance. factor is a rate per second, e.g. 1.0 covers about 63% in a second and 3.0 about
+    // 95%. dt is in seconds so this is frame rate independent.
+    pub fn smooth_follow(&mut self, target: glam::Vec3, factor: f32, dt: f32) {
+        let t = 1.0 - (-factor * dt).exp();
+        self.set_position(self.position.lerp(target, t));
+    }
+
+    pub fn lerp_vertical_fov(&mut self, target: Angle, t: f32) {
+        let t = t.clamp(0.0, 1.0);
+        let fov = self.fov.as_rad() + (target.as_rad() - self.fov.as_rad()) * t;
+        self.set_vertical_fov(Angle::rad(fov));
+    }
+
     fn compute_view_proj_matrix(&mut self) {
-        let view_matrix =
-            glam::Mat4::from_translation(glam::vec3(0.0, 0.0, -self.working_distance()));
+        let view_matrix = glam::Mat4::from_translation(
+            -self.position - glam::vec3(0.0, 0.0, self.working_distance()),
+        );
         let proj_matrix = glam::Mat4::perspective_rh_gl(
             self.fov.as_rad(),
             self.aspect_ratio,
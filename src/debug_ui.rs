@@ -1,32 +1,129 @@
 use std::{
-    collections::BTreeMap,
-    sync::{Arc, RwLock},
+    collections::{BTreeMap, VecDeque},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, RwLock},
+    time::Duration,
 };
 
 use egui::{Color32, RichText};
+use k9_proc_macros::console_command_internal;
 use sdl2::clipboard::ClipboardUtil;
 
-pub use self::console::ConsoleCommand;
-pub(super) use self::console::DebugConsoleLogger;
-use self::{console::DebugConsole, egui_render_core::EguiRenderCore};
-
-const BG_COLOUR: Color32 = Color32::from_rgb(26, 0, 15);
-const BG_LIGHTER: Color32 = Color32::from_rgb(52, 1, 29);
-const ACCENT_COLOUR: Color32 = Color32::from_rgb(252, 11, 146);
-const ACCENT_DARKER: Color32 = Color32::from_rgb(208, 3, 118);
-const ACCENT_DARKEST: Color32 = Color32::from_rgb(156, 2, 88);
-const TEXT_COLOUR: Color32 = Color32::from_rgb(255, 231, 244);
-const DIM_TEXT_COLOUR: Color32 = Color32::from_rgb(172, 130, 153);
-const OFF_ACCENT_COLOUR: Color32 = Color32::from_rgb(11, 252, 117);
-//const OFF2_ACCENT_COLOUR: Color32 = Color32::from_rgb(11, 252, 237);
-//const OFF3_ACCENT_COLOUR: Color32 = Color32::from_rgb(252, 117, 11);
-const OFF_BG_COLOUR: Color32 = Color32::from_rgb(16, 27, 36);
-// 18 12 8
+pub use self::console::{ConsoleCommand, LogLevelColours, LogLevelTheme};
+pub(super) use self::console::{DebugConsoleLogger, TerminalLogger};
+use self::{
+    console::DebugConsole,
+    egui_render_core::{CallbackFn, EguiRenderCore},
+};
+use crate::asset::resolve_asset;
 
 const BANNER_HEIGHT: f32 = 50.0;
+const BANNER_LOGO_HEIGHT: f32 = 36.0;
+const FRAME_GRAPH_SAMPLES: usize = 120;
+const FRAME_GRAPH_WIDTH: f32 = 180.0;
+const DEFAULT_UI_OPACITY: f32 = 0.80;
+// keys in the window state file are namespaced, debug windows under this prefix and k9's own
+// entries under "k9/", so no window id can collide with them
+const WINDOW_STATE_PREFIX: &str = "window/";
+// the banner's opacity and scale are saved alongside the debug windows' states under this key
+const UI_SETTINGS_STATE_KEY: &str = "k9/debug_ui";
+// key bindings from the bind command are saved there too, one "<key>\t<command line>" per line
+const KEY_BINDINGS_STATE_KEY: &str = "k9/key_bindings";
 
 pub mod console;
 mod egui_render_core;
+mod window_state;
+
+// what's shown at the left of the debug ui banner, the logo is scaled to the title's height
+pub struct DebugUiBanner {
+    pub title: String,
+    pub logo: Option<PathBuf>,
+}
+impl Default for DebugUiBanner {
+    fn default() -> Self {
+        Self {
+            title: "k9".to_owned(),
+            logo: None,
+        }
+    }
+}
+
+// the debug ui's palette, widgets are filled with the accent shades and the off colours are used
+// for highlights that need to stand apart from the accent
+#[derive(Clone)]
+pub struct ConsoleTheme {
+    pub bg: Color32,
+    pub bg_lighter: Color32,
+    pub accent: Color32,
+    pub accent_darker: Color32,
+    pub accent_darkest: Color32,
+    pub text: Color32,
+    pub dim_text: Color32,
+    pub off_accent: Color32,
+    pub off_bg: Color32,
+}
+impl ConsoleTheme {
+    // white on black with a yellow accent
+    pub fn high_contrast() -> Self {
+        Self {
+            bg: Color32::from_rgb(0, 0, 0),
+            bg_lighter: Color32::from_rgb(32, 32, 32),
+            accent: Color32::from_rgb(255, 214, 0),
+            accent_darker: Color32::from_rgb(178, 150, 0),
+            accent_darkest: Color32::from_rgb(96, 80, 0),
+            text: Color32::from_rgb(255, 255, 255),
+            dim_text: Color32::from_rgb(210, 210, 210),
+            off_accent: Color32::from_rgb(0, 230, 255),
+            off_bg: Color32::from_rgb(16, 16, 16),
+        }
+    }
+
+    // blue and orange from the okabe-ito palette, which stay distinct for the common forms of
+    // colour blindness
+    pub fn colourblind() -> Self {
+        Self {
+            bg: Color32::from_rgb(8, 16, 28),
+            bg_lighter: Color32::from_rgb(20, 36, 56),
+            accent: Color32::from_rgb(86, 180, 233),
+            accent_darker: Color32::from_rgb(0, 114, 178),
+            accent_darkest: Color32::from_rgb(0, 70, 110),
+            text: Color32::from_rgb(240, 240, 240),
+            dim_text: Color32::from_rgb(165, 175, 185),
+            off_accent: Color32::from_rgb(230, 159, 0),
+            off_bg: Color32::from_rgb(14, 22, 32),
+        }
+    }
+}
+impl Default for ConsoleTheme {
+    fn default() -> Self {
+        Self {
+            bg: Color32::from_rgb(26, 0, 15),
+            bg_lighter: Color32::from_rgb(52, 1, 29),
+            accent: Color32::from_rgb(252, 11, 146),
+            accent_darker: Color32::from_rgb(208, 3, 118),
+            accent_darkest: Color32::from_rgb(156, 2, 88),
+            text: Color32::from_rgb(255, 231, 244),
+            dim_text: Color32::from_rgb(172, 130, 153),
+            off_accent: Color32::from_rgb(11, 252, 117),
+            off_bg: Color32::from_rgb(16, 27, 36),
+        }
+    }
+}
+
+// names accepted by the set_theme command
+const THEME_PRESETS: [&str; 3] = ["default", "high_contrast", "colourblind"];
+
+fn theme_preset(name: &str) -> Option<(ConsoleTheme, LogLevelTheme)> {
+    match name {
+        "default" => Some((ConsoleTheme::default(), LogLevelTheme::default())),
+        "high_contrast" => Some((
+            ConsoleTheme::high_contrast(),
+            LogLevelTheme::high_contrast(),
+        )),
+        "colourblind" => Some((ConsoleTheme::colourblind(), LogLevelTheme::colourblind())),
+        _ => None,
+    }
+}
 
 pub struct EguiDebugUi {
     egui_core: EguiRenderCore,
@@ -34,75 +131,337 @@ pub struct EguiDebugUi {
     mouse_pos: egui::Pos2,
     ui_scale: f32,
     live_ui_scale: f32,
+    default_ui_scale: f32,
     ui_opacity: f32,
     visuals: egui::Visuals,
+    frame_times: VecDeque<Duration>,
+    frame_budget: Duration,
+    show_frame_graph: bool,
+    banner_title: String,
+    banner_logo: Option<egui::TextureHandle>,
+    // draws the raw mouse position next to the cursor, toggled by the show_mouse_pos command
+    show_mouse_pos: Arc<Mutex<bool>>,
+    theme: ConsoleTheme,
+    // set by the set_theme command, applied at the start of the next draw
+    pending_theme: Arc<Mutex<Option<(ConsoleTheme, LogLevelTheme)>>>,
+    // sdl key name to the console line it runs, see run_key_binding
+    key_bindings: Arc<Mutex<BTreeMap<String, String>>>,
 }
 
 impl EguiDebugUi {
     pub fn new(
         glow: &glow::Context,
         default_ui_scale: f32,
-        console_commands: BTreeMap<String, ConsoleCommand>,
+        window_id: u32,
+        mut console_commands: BTreeMap<String, ConsoleCommand>,
         debug_windows: BTreeMap<String, Box<dyn console::DebugUiWindow>>,
+        banner: DebugUiBanner,
+        theme: ConsoleTheme,
+        level_theme: LogLevelTheme,
+        history_path: Option<PathBuf>,
     ) -> Self {
         let mouse_pos = egui::pos2(-100.0, -100.0); // offscreen so that it doesn't show until we get a valid mouse pos
 
-        let mut visuals = egui::Visuals::dark();
-        visuals.override_text_color = Some(TEXT_COLOUR);
-        visuals.window_stroke = egui::Stroke::new(0.8, ACCENT_COLOUR);
-        visuals.extreme_bg_color = BG_LIGHTER;
-        visuals.widgets.inactive.bg_fill = ACCENT_DARKEST;
-        visuals.widgets.hovered.bg_fill = ACCENT_DARKER;
-        visuals.widgets.active.bg_fill = ACCENT_COLOUR;
-        visuals.widgets.noninteractive.bg_stroke = egui::Stroke::new(1.0, ACCENT_COLOUR);
-
-        let mut shadow = egui::epaint::Shadow::NONE;
-        shadow.extrusion = 5.0;
-        visuals.window_shadow = shadow;
+        let visuals = theme_visuals(&theme);
 
-        let egui_core = EguiRenderCore::new(glow, default_ui_scale);
+        let egui_core = EguiRenderCore::new(glow, default_ui_scale, window_id);
         egui_core.ctx.set_visuals(visuals.clone());
 
+        let banner_logo = banner
+            .logo
+            .and_then(|path| match image::open(resolve_asset(&path)) {
+                Ok(x) => {
+                    let size = [x.width() as usize, x.height() as usize];
+                    let image = egui::ColorImage::from_rgba_unmultiplied(size, &x.into_rgba8());
+                    Some(egui_core.ctx.load_texture(
+                        "k9_debug_ui_banner_logo",
+                        image,
+                        egui::TextureOptions::LINEAR,
+                    ))
+                }
+                Err(e) => {
+                    log::error!("couldn't open debug ui banner logo {path:?}: {e}");
+                    None
+                }
+            });
+
+        let show_mouse_pos = Arc::new(Mutex::new(false));
+        {
+            let val = show_mouse_pos.clone();
+            let cc = console_command_internal!(
+                "shows the mouse position next to the cursor while the debug ui is open.",
+                { value: bool },
+                |ccf, value| {
+                    *val.lock().unwrap() = value;
+                    Ok(())
+                }
+            );
+            console_commands
+                .entry("show_mouse_pos".to_owned())
+                .and_modify(|_| log::warn!("console command 'show_mouse_pos' was overwritten."))
+                .or_insert(cc);
+        }
+
+        let pending_theme = Arc::new(Mutex::new(None));
+        {
+            let val = pending_theme.clone();
+            let cc = console_command_internal!(
+                "switches the debug ui to a bundled theme: default, high_contrast or colourblind.",
+                { name: String },
+                |ccf, name: String| {
+                    let preset = theme_preset(&name).ok_or(format!(
+                        "unknown theme '{name}', expected one of: {}",
+                        THEME_PRESETS.join(", ")
+                    ))?;
+                    *val.lock().unwrap() = Some(preset);
+                    Ok(())
+                }
+            );
+            console_commands
+                .entry("set_theme".to_owned())
+                .and_modify(|_| log::warn!("console command 'set_theme' was overwritten."))
+                .or_insert(cc);
+        }
+
+        let key_bindings = Arc::new(Mutex::new(BTreeMap::new()));
+        {
+            let bindings = key_bindings.clone();
+            let cc = console_command_internal!(
+                "binds a key to a console line, run when the key is pressed outside of text entry. without a command it shows the key's binding.",
+                { key: String "sdl key name, e.g. F5 or Left Shift", opt command: String },
+                |mut ccf: console::ConsoleCommandInterface, key: String, command: Option<String>| {
+                    let key = bindable_key_name(&key)?;
+                    let mut bindings = bindings.lock().unwrap();
+                    match command {
+                        Some(command) => {
+                            bindings.insert(key, command);
+                        }
+                        None => match bindings.get(&key) {
+                            Some(command) => ccf.println(format!("{key} runs: {command}")),
+                            None => ccf.println(format!("{key} isn't bound")),
+                        },
+                    }
+                    Ok(())
+                }
+            );
+            console_commands
+                .entry("bind".to_owned())
+                .and_modify(|_| log::warn!("console command 'bind' was overwritten."))
+                .or_insert(cc);
+
+            let bindings = key_bindings.clone();
+            let cc = console_command_internal!(
+                "removes a key's binding.",
+                { key: String },
+                |ccf, key: String| {
+                    let key = bindable_key_name(&key)?;
+                    match bindings.lock().unwrap().remove(&key) {
+                        Some(_) => Ok(()),
+                        None => Err(format!("{key} isn't bound")),
+                    }
+                }
+            );
+            console_commands
+                .entry("unbind".to_owned())
+                .and_modify(|_| log::warn!("console command 'unbind' was overwritten."))
+                .or_insert(cc);
+        }
+
         Self {
             egui_core,
             mouse_pos,
             ui_scale: default_ui_scale,
             live_ui_scale: default_ui_scale,
-            ui_opacity: 0.80,
+            default_ui_scale,
+            ui_opacity: DEFAULT_UI_OPACITY,
             visuals,
-            console_core: DebugConsole::new(console_commands, debug_windows),
+            console_core: DebugConsole::new(
+                console_commands,
+                debug_windows,
+                level_theme,
+                history_path,
+            ),
+            frame_times: VecDeque::with_capacity(FRAME_GRAPH_SAMPLES),
+            frame_budget: Duration::ZERO,
+            show_frame_graph: true,
+            banner_title: banner.title,
+            banner_logo,
+            show_mouse_pos,
+            theme,
+            pending_theme,
+            key_bindings,
+        }
+    }
+
+    // runs the console line bound to the key, if any. the caller decides whether keys are free,
+    // i.e. not going to a text field.
+    pub fn run_key_binding(&mut self, keycode: sdl2::keyboard::Keycode) {
+        let line = self
+            .key_bindings
+            .lock()
+            .unwrap()
+            .get(&keycode.name())
+            .cloned();
+        if let Some(line) = line {
+            log::info!("Execute: {line}");
+            self.console_core.execute(&line);
+        }
+    }
+
+    // paints the ui into its own texture which is then composited over the frame, so the ui
+    // can be captured or processed separately from the game
+    pub fn set_render_offscreen(&mut self, glow: &glow::Context, enabled: bool) {
+        self.egui_core.set_render_offscreen(glow, enabled);
+    }
+
+    // the last frame of the ui when rendering offscreen, premultiplied alpha
+    pub fn ui_texture(&self) -> Option<glow::NativeTexture> {
+        self.egui_core.offscreen_texture()
+    }
+
+    // a missing file is fine, it's created on the first save
+    pub fn load_window_states(&mut self, path: &Path) {
+        if !path.exists() {
+            return;
+        }
+        match window_state::read(path) {
+            Ok(mut states) => {
+                if let Some(state) = states.remove(UI_SETTINGS_STATE_KEY) {
+                    self.restore_ui_settings(&state);
+                }
+                if let Some(state) = states.remove(KEY_BINDINGS_STATE_KEY) {
+                    self.restore_key_bindings(&state);
+                }
+                let window_states = states
+                    .into_iter()
+                    .filter_map(|(key, state)| {
+                        Some((key.strip_prefix(WINDOW_STATE_PREFIX)?.to_owned(), state))
+                    })
+                    .collect();
+                self.console_core.restore_window_states(window_states);
+            }
+            Err(e) => log::error!("couldn't read debug window states from {path:?}: {e}"),
+        }
+    }
+
+    pub fn save_window_states(&self, path: &Path) {
+        let mut states: BTreeMap<String, String> = self
+            .console_core
+            .window_states()
+            .into_iter()
+            .map(|(id, state)| (format!("{WINDOW_STATE_PREFIX}{id}"), state))
+            .collect();
+        states.insert(
+            UI_SETTINGS_STATE_KEY.to_owned(),
+            format!("{} {}", self.ui_opacity, self.ui_scale),
+        );
+        let bindings = self.key_bindings.lock().unwrap();
+        if !bindings.is_empty() {
+            let lines: Vec<String> = bindings
+                .iter()
+                .map(|(key, line)| format!("{key}\t{line}"))
+                .collect();
+            states.insert(KEY_BINDINGS_STATE_KEY.to_owned(), lines.join("\n"));
+        }
+        if let Err(e) = window_state::write(path, &states) {
+            log::error!("couldn't write debug window states to {path:?}: {e}");
+        }
+    }
+
+    // "<opacity> <scale>", anything else is ignored and the defaults are kept
+    fn restore_ui_settings(&mut self, state: &str) {
+        let settings = state
+            .split_once(' ')
+            .and_then(|(o, s)| Some((o.parse::<f32>().ok()?, s.parse::<f32>().ok()?)));
+        match settings {
+            Some((opacity, scale)) => {
+                self.ui_opacity = opacity.clamp(0.0, 1.0);
+                self.set_ui_scale(scale.clamp(0.5, 2.0));
+            }
+            None => log::warn!("ignoring bad debug ui settings '{state}'"),
+        }
+    }
+
+    // bindings for keys sdl no longer knows are dropped
+    fn restore_key_bindings(&mut self, state: &str) {
+        let mut bindings = self.key_bindings.lock().unwrap();
+        for line in state.lines() {
+            match line.split_once('\t') {
+                Some((key, command)) => match bindable_key_name(key) {
+                    Ok(key) => {
+                        bindings.insert(key, command.to_owned());
+                    }
+                    Err(e) => log::warn!("ignoring key binding: {e}"),
+                },
+                None => log::warn!("ignoring bad key binding '{line}'"),
+            }
         }
     }
 
+    fn set_ui_scale(&mut self, scale: f32) {
+        self.ui_scale = scale;
+        self.live_ui_scale = scale;
+        self.egui_core.ctx.set_pixels_per_point(scale);
+    }
+
     pub fn set_console_focus(&mut self) {
         self.console_core.set_console_focus = true;
     }
 
+    // every registered console command in name order, with its description, aliases and
+    // arguments but not its callback
+    pub fn console_commands(&self) -> impl Iterator<Item = (&str, &ConsoleCommand)> {
+        self.console_core.commands()
+    }
+
+    // budget is the target frame time, samples over it are flagged in the graph
+    pub fn push_frame_time(&mut self, frame_time: Duration, budget: Duration) {
+        if self.frame_times.len() == FRAME_GRAPH_SAMPLES {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(frame_time);
+        self.frame_budget = budget;
+    }
+
     pub fn wants_keyboard_input(&self) -> bool {
         self.egui_core.ctx.wants_keyboard_input()
     }
 
+    // unlike wants_keyboard_input this is only true for the console's command entry, not any
+    // egui text field. it's only meaningful while the debug ui is being drawn.
+    pub fn console_has_focus(&self) -> bool {
+        self.console_core.has_focus()
+    }
+
+    // debug_draw lets the caller add its own egui after the console, e.g. each system's
     pub fn draw(
         &mut self,
         screen_dimensions: (u32, u32),
         logger: &Arc<RwLock<Vec<console::DebugLogRecord>>>,
+        debug_draw: &mut dyn FnMut(&egui::Context),
     ) {
+        if let Some((theme, level_theme)) = self.pending_theme.lock().unwrap().take() {
+            self.visuals = theme_visuals(&theme);
+            self.theme = theme;
+            self.console_core.set_level_theme(level_theme);
+        }
+
         // setup visuals
-        self.visuals.window_fill =
-            Color32::from_rgba_unmultiplied(BG_COLOUR.r(), BG_COLOUR.g(), BG_COLOUR.b(), {
-                (self.ui_opacity * 255.0) as u8
-            });
+        let bg = self.theme.bg;
+        self.visuals.window_fill = Color32::from_rgba_unmultiplied(bg.r(), bg.g(), bg.b(), {
+            (self.ui_opacity * 255.0) as u8
+        });
         self.egui_core.ctx.set_visuals(self.visuals.clone());
 
         let w = screen_dimensions.0 as f32 / self.ui_scale;
         let _h = screen_dimensions.1 as f32 / self.ui_scale;
-        let banner_bg =
-            Color32::from_rgba_unmultiplied(BG_COLOUR.r(), BG_COLOUR.g(), BG_COLOUR.b(), 128);
+        let banner_bg = Color32::from_rgba_unmultiplied(bg.r(), bg.g(), bg.b(), 128);
 
-        // draw banner
+        // draw banner, the context is a handle so the panel can set the ui scale through self
+        let ctx = self.egui_core.ctx.clone();
         egui::TopBottomPanel::top("egui_debug_ui_top_panel")
             .frame(egui::Frame::none())
-            .show(&self.egui_core.ctx, |ui| {
+            .show(&ctx, |ui| {
                 let painter = ui.painter();
                 painter.rect(
                     egui::Rect::from_two_pos(
@@ -111,23 +470,52 @@ impl EguiDebugUi {
                     ),
                     egui::Rounding::none(),
                     banner_bg,
-                    egui::Stroke::new(1.0, ACCENT_COLOUR),
+                    egui::Stroke::new(1.0, self.theme.accent),
                 );
 
                 ui.horizontal(|ui| {
+                    if let Some(logo) = &self.banner_logo {
+                        let logo_size = logo.size_vec2();
+                        let logo_width = logo_size.x * BANNER_LOGO_HEIGHT / logo_size.y.max(1.0);
+                        ui.add_sized(
+                            [logo_width, BANNER_HEIGHT],
+                            egui::Image::new(logo.id(), [logo_width, BANNER_LOGO_HEIGHT]),
+                        );
+                    }
+                    // doesn't wrap so longer titles widen the label instead of breaking onto
+                    // a second line
                     ui.add_sized(
                         [64.0, BANNER_HEIGHT],
-                        egui::Label::new(RichText::new("k9").size(36.0).strong()),
+                        egui::Label::new(RichText::new(&self.banner_title).size(36.0).strong())
+                            .wrap(false),
                     );
                 });
 
+                if self.show_frame_graph {
+                    let graph_rect = egui::Rect::from_two_pos(
+                        egui::pos2(w - 460.0 - FRAME_GRAPH_WIDTH, 8.0),
+                        egui::pos2(w - 460.0, BANNER_HEIGHT - 8.0),
+                    );
+                    draw_frame_graph(
+                        ui,
+                        graph_rect,
+                        &self.frame_times,
+                        self.frame_budget,
+                        &self.theme,
+                    );
+                }
+
                 ui.allocate_ui_at_rect(
                     egui::Rect::from_two_pos(
-                        egui::pos2(w - 310.0, 6.0),
+                        egui::pos2(w - 450.0, 6.0),
                         egui::pos2(w, BANNER_HEIGHT),
                     ),
                     |ui| {
                         ui.horizontal(|ui| {
+                            ui.vertical(|ui| {
+                                ui.label("frame graph");
+                                ui.checkbox(&mut self.show_frame_graph, "");
+                            });
                             ui.vertical(|ui| {
                                 ui.label("ui opacity");
                                 ui.add(egui::Slider::new(&mut self.ui_opacity, 0.0..=1.0));
@@ -142,27 +530,39 @@ impl EguiDebugUi {
                                     ui.ctx().set_pixels_per_point(self.ui_scale);
                                 }
                             });
+                            ui.vertical(|ui| {
+                                ui.label("");
+                                if ui.button("reset").clicked() {
+                                    self.ui_opacity = DEFAULT_UI_OPACITY;
+                                    self.set_ui_scale(self.default_ui_scale);
+                                }
+                            });
                         })
                     },
                 );
 
-                let mut debug_mouse_pos = self.mouse_pos;
-                debug_mouse_pos.x = debug_mouse_pos.x / self.ui_scale + 16.0;
-                debug_mouse_pos.y = debug_mouse_pos.y / self.ui_scale + 9.0;
-                ui.ctx().debug_painter().debug_text(
-                    debug_mouse_pos,
-                    egui::Align2::LEFT_TOP,
-                    TEXT_COLOUR,
-                    format!("{:?}", self.mouse_pos),
-                );
+                if *self.show_mouse_pos.lock().unwrap() {
+                    let mut debug_mouse_pos = self.mouse_pos;
+                    debug_mouse_pos.x = debug_mouse_pos.x / self.ui_scale + 16.0;
+                    debug_mouse_pos.y = debug_mouse_pos.y / self.ui_scale + 9.0;
+                    ui.ctx().debug_painter().debug_text(
+                        debug_mouse_pos,
+                        egui::Align2::LEFT_TOP,
+                        self.theme.text,
+                        format!("{:?}", self.mouse_pos),
+                    );
+                }
             });
 
         egui::CentralPanel::default()
             .frame(egui::Frame::none())
             .show(&self.egui_core.ctx, |ui| {
                 // draw console
-                self.console_core.draw(ui, logger, self.ui_opacity);
+                self.console_core
+                    .draw(ui, logger, self.ui_opacity, &self.theme);
             });
+
+        debug_draw(&self.egui_core.ctx);
     }
 
     pub fn render(
@@ -173,14 +573,27 @@ impl EguiDebugUi {
         screen_dimensions: (u32, u32),
         window_has_focus: bool,
         logger: &Arc<RwLock<Vec<console::DebugLogRecord>>>,
+        debug_draw: &mut dyn FnMut(&egui::Context),
     ) {
+        // window pixels, the overlay scales it down to ui points when drawing
+        for event in sdl_events {
+            if let sdl2::event::Event::MouseMotion {
+                window_id, x, y, ..
+            } = event
+            {
+                if *window_id == self.egui_core.window_id() {
+                    self.mouse_pos = egui::pos2(*x as f32, *y as f32);
+                }
+            }
+        }
+
         self.egui_core.begin_frame(
             window_has_focus,
             sdl_events,
             screen_dimensions,
             clipboard_util,
         );
-        self.draw(screen_dimensions, logger);
+        self.draw(screen_dimensions, logger, debug_draw);
         let (primitives, tex_delta, plat_output) = self.egui_core.end_frame();
         self.egui_core
             .handle_platform_output(plat_output, clipboard_util);
@@ -188,3 +601,106 @@ impl EguiDebugUi {
             .render(glow, screen_dimensions, primitives, tex_delta);
     }
 }
+
+// wraps a closure so it can do its own gl rendering inside an egui rect, e.g. a 3d preview
+// in a debug window: `ui.painter().add(k9::debug_ui::gl_paint_callback(rect, |info, glow| ..))`.
+// the viewport is already set to the rect, egui's own gl state is restored afterwards.
+pub fn gl_paint_callback<F>(rect: egui::Rect, f: F) -> egui::PaintCallback
+where
+    F: Fn(egui::PaintCallbackInfo, &glow::Context) + Sync + Send + 'static,
+{
+    egui::PaintCallback {
+        rect,
+        callback: Arc::new(CallbackFn::new(f)),
+    }
+}
+
+// the sdl name for a key, so differently typed names of the same key share a binding. backquote
+// opens the console, so it can't be bound.
+fn bindable_key_name(name: &str) -> Result<String, String> {
+    let keycode = sdl2::keyboard::Keycode::from_name(name).ok_or(format!(
+        "unknown key '{name}', expected an sdl key name like F5 or Left Shift"
+    ))?;
+    if keycode == sdl2::keyboard::Keycode::Backquote {
+        return Err("the backquote key toggles the console and can't be bound".to_owned());
+    }
+    Ok(keycode.name())
+}
+
+fn theme_visuals(theme: &ConsoleTheme) -> egui::Visuals {
+    let mut visuals = egui::Visuals::dark();
+    visuals.override_text_color = Some(theme.text);
+    visuals.window_stroke = egui::Stroke::new(0.8, theme.accent);
+    visuals.extreme_bg_color = theme.bg_lighter;
+    visuals.widgets.inactive.bg_fill = theme.accent_darkest;
+    visuals.widgets.hovered.bg_fill = theme.accent_darker;
+    visuals.widgets.active.bg_fill = theme.accent;
+    visuals.widgets.noninteractive.bg_stroke = egui::Stroke::new(1.0, theme.accent);
+
+    let mut shadow = egui::epaint::Shadow::NONE;
+    shadow.extrusion = 5.0;
+    visuals.window_shadow = shadow;
+
+    visuals
+}
+
+fn draw_frame_graph(
+    ui: &egui::Ui,
+    rect: egui::Rect,
+    frame_times: &VecDeque<Duration>,
+    budget: Duration,
+    theme: &ConsoleTheme,
+) {
+    let painter = ui.painter();
+    painter.rect(
+        rect,
+        egui::Rounding::none(),
+        theme.off_bg,
+        egui::Stroke::new(1.0, theme.accent_darkest),
+    );
+
+    if budget.is_zero() {
+        return;
+    }
+
+    // the budget sits at half height so that overruns up to 2x are still visible
+    let scale = rect.height() / (budget.as_secs_f32() * 2.0);
+    let bar_w = rect.width() / FRAME_GRAPH_SAMPLES as f32;
+    let first_x = rect.right() - bar_w * frame_times.len() as f32;
+
+    for (i, frame_time) in frame_times.iter().enumerate() {
+        let bar_h = (frame_time.as_secs_f32() * scale).min(rect.height());
+        let x = first_x + bar_w * i as f32;
+        let colour = if *frame_time > budget {
+            theme.accent
+        } else {
+            theme.off_accent
+        };
+
+        painter.rect_filled(
+            egui::Rect::from_two_pos(
+                egui::pos2(x, rect.bottom() - bar_h),
+                egui::pos2(x + bar_w, rect.bottom()),
+            ),
+            egui::Rounding::none(),
+            colour,
+        );
+    }
+
+    let budget_y = rect.bottom() - rect.height() / 2.0;
+    painter.hline(
+        rect.x_range(),
+        budget_y,
+        egui::Stroke::new(1.0, theme.dim_text),
+    );
+
+    if let Some(last) = frame_times.back() {
+        painter.text(
+            rect.left_top() + egui::vec2(3.0, 1.0),
+            egui::Align2::LEFT_TOP,
+            format!("{:.2?}", last),
+            egui::FontId::monospace(10.0),
+            theme.text,
+        );
+    }
+}
@@ -1,11 +1,17 @@
 use std::{
     collections::{BTreeMap, VecDeque},
+    io::Write,
+    path::{Path, PathBuf},
     sync::{Arc, Mutex, RwLock},
 };
 
 use bnf::{ParseTree, ParseTreeNode};
 use egui::{
-    epaint::text::{cursor::RCursor, TextWrapping},
+    epaint::text::{
+        cursor::{CCursor, RCursor},
+        TextWrapping,
+    },
+    text_edit::CCursorRange,
     text::LayoutJob,
     Align, Color32, FontId, Frame, RichText, Sense, TextFormat,
 };
@@ -13,9 +19,48 @@ use egui_extras::Column;
 use k9_proc_macros::console_command_internal;
 use time::OffsetDateTime;
 
-use super::{DIM_TEXT_COLOUR, OFF_ACCENT_COLOUR, OFF_BG_COLOUR, TEXT_COLOUR};
+use super::ConsoleTheme;
 
 type Flag = bool;
+
+const HISTORY_LIMIT: usize = 200;
+const OUTPUT_LIMIT: usize = 500;
+const LOG_ROW_TEXT_LIMIT: usize = 512;
+const LOG_LEVELS: [log::Level; 5] = [
+    log::Level::Error,
+    log::Level::Warn,
+    log::Level::Info,
+    log::Level::Debug,
+    log::Level::Trace,
+];
+
+// summary of console_command.bnf plus the line splitting done before it, see split_command_line
+const SYNTAX_HELP: &str = "\
+command [arguments..], arguments are separated by whitespace:
+  value          fills the command's arguments in order, required ones first
+  name: value    sets an argument by name
+  --name         sets a flag argument, flags left out are false
+values are plain text, or quoted to hold spaces, ':' and ',', \"\" is an empty string.
+list arguments take values separated by ',' or whitespace, \"\" is an empty list.
+plain values can't start with '-', quote them instead: \"-5\".
+\\ escapes a quote, space or backslash inside a value.
+';' runs several commands from one line and '//' starts a comment, neither counts inside quotes.";
+const SYNTAX_EXAMPLES: [&str; 7] = [
+    "show_grid true",
+    "show_grid value: 1",
+    "gl_debug high sync: true",
+    "echo \"hello world\"",
+    "echo \"-5\"",
+    "highlight ids: 3, 7, 9",
+    "show_grid true; show_mouse_pos true // both at once",
+];
+
+const COMMAND_GRAMMAR: &str = include_str!("./console_command.bnf");
+
+// a record logged with this structured field, e.g. log::info!(console_command = "quit"; ..), can
+// be clicked in the log to put that command into the entry ready to run
+pub const CONSOLE_COMMAND_FIELD: &str = "console_command";
+
 pub(super) struct DebugConsole {
     console_text: String,
     record_windows: Option<BTreeMap<usize, RecordWindow>>,
@@ -23,21 +68,55 @@ pub(super) struct DebugConsole {
     console_has_focus: bool,
     command_grammar: bnf::Grammar,
     debug_console_commands: Arc<Mutex<bool>>,
+    autocomplete_wrap: Arc<Mutex<bool>>,
+    enter_runs_autocomplete: Arc<Mutex<bool>>,
     selected_autocomplete_cmd: Option<(String, usize)>,
     preview_autocomplete_cmds: Vec<String>,
+    preview_autocomplete_total: usize,
+    autocomplete_limit: Arc<Mutex<i32>>,
+    chain_stops_on_error: Arc<Mutex<bool>>,
+    case_insensitive: Arc<Mutex<bool>>,
+    fuzzy_autocomplete: Arc<Mutex<bool>>,
     draw_preview_commands_list: bool,
     last_console_window_height: f32,
     console_commands: BTreeMap<String, ConsoleCommand>,
+    // alias to command name, rebuilt whenever a command is registered
+    command_aliases: BTreeMap<String, String>,
+    // aliases made with the alias command, name to the command it runs
+    user_aliases: BTreeMap<String, String>,
     debug_windows: BTreeMap<String, (bool, Box<dyn DebugUiWindow>)>,
     last_cursor_idx: usize,
+    // warn/error record last jumped to with F3/Shift+F3, highlighted in the log
+    log_cursor: Option<usize>,
+    // records before this index are hidden by the clear command. they're kept in the shared list,
+    // other users of the log still see them.
+    log_cleared_before: usize,
+    // set by the clear command, the console only sees the records while drawing
+    clear_log: Flag,
+    // levels shown in the log, toggled by the buttons above it. only the view is filtered.
+    log_level_filter: BTreeMap<log::Level, bool>,
+    // saved states of windows that haven't been added yet, applied when they are
+    pending_window_states: BTreeMap<String, String>,
+    // moves the entry's cursor to the end of console_text on the next draw
+    console_cursor_to_end: Flag,
+    // commands run this session, oldest first. history_cursor is the entry recalled with the
+    // arrow keys, None while the text is the user's own.
+    history: Vec<String>,
+    history_cursor: Option<usize>,
+    // run commands are appended here when set, one per line
+    history_path: Option<PathBuf>,
+    level_theme: LogLevelTheme,
+    // lines printed by commands and their errors, shown above the entry apart from the log
+    output: Vec<ConsoleOutputLine>,
 }
 impl DebugConsole {
     pub fn new(
         mut console_commands: BTreeMap<String, ConsoleCommand>,
         debug_windows: BTreeMap<String, Box<dyn DebugUiWindow>>,
+        level_theme: LogLevelTheme,
+        history_path: Option<PathBuf>,
     ) -> Self {
-        const GRAMMAR: &'static str = include_str!("./console_command.bnf");
-        let command_grammar: bnf::Grammar = GRAMMAR.parse().unwrap();
+        let command_grammar: bnf::Grammar = COMMAND_GRAMMAR.parse().unwrap();
 
         // setup some console commands
         let debug_console_commands = Arc::new(Mutex::new(false));
@@ -60,9 +139,286 @@ impl DebugConsole {
                 .or_insert(cc_debug_console_command);
         }
 
+        // arrow key navigation through the autocomplete list wraps around at the ends by default
+        let autocomplete_wrap = Arc::new(Mutex::new(true));
+        {
+            let val = autocomplete_wrap.clone();
+            let cc_autocomplete_wrap =
+                console_command_internal!(
+                    "sets whether arrow key navigation of the autocomplete list wraps around at the ends.",
+                    { value: bool },
+                    |ccf, value| {
+                        *val.lock().unwrap() = value;
+                        Ok(())
+                    }
+                );
+            console_commands
+                .entry("k9_console_autocomplete_wrap".to_owned())
+                .and_modify(|_| {
+                    log::warn!("console command 'k9_console_autocomplete_wrap' was overwritten.")
+                })
+                .or_insert(cc_autocomplete_wrap);
+        }
+
+        // enter with a selected autocomplete completes the command and runs it, when disabled
+        // it only completes and a second enter is needed to run it
+        let enter_runs_autocomplete = Arc::new(Mutex::new(true));
+        {
+            let val = enter_runs_autocomplete.clone();
+            let cc_enter_runs_autocomplete =
+                console_command_internal!(
+                    "sets whether enter runs the selected autocomplete or only completes it.",
+                    { value: bool },
+                    |ccf, value| {
+                        *val.lock().unwrap() = value;
+                        Ok(())
+                    }
+                );
+            console_commands
+                .entry("k9_console_enter_runs_autocomplete".to_owned())
+                .and_modify(|_| {
+                    log::warn!("console command 'k9_console_enter_runs_autocomplete' was overwritten.")
+                })
+                .or_insert(cc_enter_runs_autocomplete);
+        }
+
+        // caps the autocomplete matches gathered per keystroke, so single letter prefixes stay cheap
+        let autocomplete_limit = Arc::new(Mutex::new(200));
+        {
+            let val = autocomplete_limit.clone();
+            let cc_autocomplete_limit =
+                console_command_internal!(
+                    "sets the maximum number of autocomplete matches shown.",
+                    { value: i32 },
+                    |ccf, value| {
+                        if value < 1 {
+                            return Err(format!("autocomplete limit must be at least 1, got {value}"));
+                        }
+                        *val.lock().unwrap() = value;
+                        Ok(())
+                    }
+                );
+            console_commands
+                .entry("k9_console_autocomplete_limit".to_owned())
+                .and_modify(|_| {
+                    log::warn!("console command 'k9_console_autocomplete_limit' was overwritten.")
+                })
+                .or_insert(cc_autocomplete_limit);
+        }
+
+        // a failed command in a ';' separated line skips the rest of the line by default
+        let chain_stops_on_error = Arc::new(Mutex::new(true));
+        {
+            let val = chain_stops_on_error.clone();
+            let cc_chain_stops_on_error =
+                console_command_internal!(
+                    "sets whether a failed command stops the rest of a ';' separated line from running.",
+                    { value: bool },
+                    |ccf, value| {
+                        *val.lock().unwrap() = value;
+                        Ok(())
+                    }
+                );
+            console_commands
+                .entry("k9_console_chain_stops_on_error".to_owned())
+                .and_modify(|_| {
+                    log::warn!("console command 'k9_console_chain_stops_on_error' was overwritten.")
+                })
+                .or_insert(cc_chain_stops_on_error);
+        }
+
+        // command names match exactly unless enabled, completions keep the registered casing
+        let case_insensitive = Arc::new(Mutex::new(false));
+        {
+            let val = case_insensitive.clone();
+            let cc_case_insensitive =
+                console_command_internal!(
+                    "sets whether command names are matched ignoring case.",
+                    aliases["k9_case_insensitive"],
+                    { value: bool },
+                    |ccf, value| {
+                        *val.lock().unwrap() = value;
+                        Ok(())
+                    }
+                );
+            console_commands
+                .entry("k9_console_case_insensitive".to_owned())
+                .and_modify(|_| {
+                    log::warn!("console command 'k9_console_case_insensitive' was overwritten.")
+                })
+                .or_insert(cc_case_insensitive);
+        }
+
+        // strict prefix matching unless enabled, fuzzy completions rank prefix matches first
+        let fuzzy_autocomplete = Arc::new(Mutex::new(false));
+        {
+            let val = fuzzy_autocomplete.clone();
+            let cc_fuzzy_autocomplete =
+                console_command_internal!(
+                    "sets whether autocomplete matches commands containing the typed letters in order, rather than only commands starting with them.",
+                    { value: bool },
+                    |ccf, value| {
+                        *val.lock().unwrap() = value;
+                        Ok(())
+                    }
+                );
+            console_commands
+                .entry("k9_console_fuzzy_autocomplete".to_owned())
+                .and_modify(|_| {
+                    log::warn!("console command 'k9_console_fuzzy_autocomplete' was overwritten.")
+                })
+                .or_insert(cc_fuzzy_autocomplete);
+        }
+
+        {
+            let cc_help =
+                console_command_internal!(
+                    "lists every command, or describes one command and its arguments.",
+                    { opt command: String "the command to describe" },
+                    |mut ccf: ConsoleCommandInterface, command: Option<String>| {
+                        ccf.actions.push(ConsoleAction::PrintHelp(command));
+                        Ok(())
+                    }
+                );
+            console_commands
+                .entry("help".to_owned())
+                .and_modify(|_| {
+                    log::warn!("console command 'help' was overwritten.")
+                })
+                .or_insert(cc_help);
+        }
+
+        {
+            let cc_syntax =
+                console_command_internal!(
+                    "explains the console's command syntax with examples.",
+                    {},
+                    |mut ccf: ConsoleCommandInterface| {
+                        let mut text = SYNTAX_HELP.to_owned();
+                        text += "\nexamples:";
+                        for example in SYNTAX_EXAMPLES {
+                            text += &format!("\n  {example}");
+                        }
+                        ccf.println(text);
+                        Ok(())
+                    }
+                );
+            console_commands
+                .entry("syntax".to_owned())
+                .and_modify(|_| {
+                    log::warn!("console command 'syntax' was overwritten.")
+                })
+                .or_insert(cc_syntax);
+        }
+
+        {
+            let cc_echo =
+                console_command_internal!(
+                    "prints text to the console.",
+                    { text: String },
+                    |mut ccf: ConsoleCommandInterface, text: String| {
+                        ccf.println(text);
+                        Ok(())
+                    }
+                );
+            console_commands
+                .entry("echo".to_owned())
+                .and_modify(|_| {
+                    log::warn!("console command 'echo' was overwritten.")
+                })
+                .or_insert(cc_echo);
+        }
+
+        {
+            let cc_clear =
+                console_command_internal!(
+                    "clears the log and command output, new records still show.",
+                    aliases["cls"],
+                    {},
+                    |mut ccf: ConsoleCommandInterface| {
+                        ccf.actions.push(ConsoleAction::Clear);
+                        Ok(())
+                    }
+                );
+            console_commands
+                .entry("clear".to_owned())
+                .and_modify(|_| {
+                    log::warn!("console command 'clear' was overwritten.")
+                })
+                .or_insert(cc_clear);
+        }
+
+        {
+            let cc_alias =
+                console_command_internal!(
+                    "gives a command another name, target can be a command or an alias.",
+                    { name: String "the new name", target: String "the command it runs" },
+                    |mut ccf: ConsoleCommandInterface, name: String, target: String| {
+                        ccf.actions.push(ConsoleAction::SetAlias(name, target));
+                        Ok(())
+                    }
+                );
+            console_commands
+                .entry("alias".to_owned())
+                .and_modify(|_| {
+                    log::warn!("console command 'alias' was overwritten.")
+                })
+                .or_insert(cc_alias);
+
+            let cc_unalias =
+                console_command_internal!(
+                    "removes an alias made with the alias command.",
+                    { name: String },
+                    |mut ccf: ConsoleCommandInterface, name: String| {
+                        ccf.actions.push(ConsoleAction::RemoveAlias(name));
+                        Ok(())
+                    }
+                );
+            console_commands
+                .entry("unalias".to_owned())
+                .and_modify(|_| {
+                    log::warn!("console command 'unalias' was overwritten.")
+                })
+                .or_insert(cc_unalias);
+        }
+
+        {
+            let cc_log_filter =
+                console_command_internal!(
+                    "shows only the given levels in the log, e.g. --error --warn. no flags shows every level.",
+                    { error: Flag, warn: Flag, info: Flag, debug: Flag, trace: Flag },
+                    |mut ccf: ConsoleCommandInterface, error: bool, warn: bool, info: bool, debug: bool, trace: bool| {
+                        let flags = [error, warn, info, debug, trace];
+                        let show_all = !flags.contains(&true);
+                        let levels = LOG_LEVELS
+                            .into_iter()
+                            .zip(flags)
+                            .map(|(level, shown)| (level, shown || show_all))
+                            .collect();
+                        ccf.actions.push(ConsoleAction::SetLogFilter(levels));
+                        Ok(())
+                    }
+                );
+            console_commands
+                .entry("k9_log_filter".to_owned())
+                .and_modify(|_| {
+                    log::warn!("console command 'k9_log_filter' was overwritten.")
+                })
+                .or_insert(cc_log_filter);
+        }
+
+        let command_aliases = build_alias_map(&console_commands);
+
         Self {
+            autocomplete_limit,
+            autocomplete_wrap,
+            chain_stops_on_error,
+            case_insensitive,
+            fuzzy_autocomplete,
             command_grammar,
             console_commands,
+            command_aliases,
+            user_aliases: BTreeMap::new(),
             console_has_focus: false,
             console_text: "".to_owned(),
             debug_console_commands,
@@ -71,13 +427,59 @@ impl DebugConsole {
                 .map(|(name, wnd)| (name, (false, wnd)))
                 .collect(),
             draw_preview_commands_list: false,
+            enter_runs_autocomplete,
             last_console_window_height: 0.0,
             preview_autocomplete_cmds: Vec::new(),
+            preview_autocomplete_total: 0,
             record_windows: Some(BTreeMap::new()),
             selected_autocomplete_cmd: None,
             set_console_focus: false,
             last_cursor_idx: 0,
+            log_cursor: None,
+            log_cleared_before: 0,
+            clear_log: false,
+            log_level_filter: LOG_LEVELS.into_iter().map(|x| (x, true)).collect(),
+            pending_window_states: BTreeMap::new(),
+            console_cursor_to_end: false,
+            history: history_path.as_deref().map(load_history).unwrap_or_default(),
+            history_cursor: None,
+            history_path,
+            level_theme,
+            output: Vec::new(),
+        }
+    }
+
+    pub fn restore_window_states(&mut self, mut states: BTreeMap<String, String>) {
+        for (id, (_, wnd)) in &mut self.debug_windows {
+            if let Some(state) = states.remove(id) {
+                wnd.restore_state(&state);
+            }
+        }
+        self.pending_window_states.append(&mut states);
+    }
+
+    // states of windows that were never added this run are carried over so they aren't lost
+    pub fn window_states(&self) -> BTreeMap<String, String> {
+        let mut states = self.pending_window_states.clone();
+        for (id, (_, wnd)) in &self.debug_windows {
+            if let Some(state) = wnd.serialize_state() {
+                states.insert(id.clone(), state);
+            }
         }
+        states
+    }
+
+    pub fn has_focus(&self) -> bool {
+        self.console_has_focus
+    }
+
+    // registered commands by name, callbacks aren't reachable through these
+    pub fn commands(&self) -> impl Iterator<Item = (&str, &ConsoleCommand)> {
+        self.console_commands.iter().map(|(name, cmd)| (name.as_str(), cmd))
+    }
+
+    pub fn set_level_theme(&mut self, level_theme: LogLevelTheme) {
+        self.level_theme = level_theme;
     }
 
     pub fn draw(
@@ -85,7 +487,14 @@ impl DebugConsole {
         ui: &mut egui::Ui,
         logger: &Arc<RwLock<Vec<DebugLogRecord>>>,
         ui_opacity: f32,
+        theme: &ConsoleTheme,
     ) {
+        if self.clear_log {
+            self.clear_log = false;
+            self.log_cleared_before = logger.read().unwrap().len();
+            self.log_cursor = None;
+        }
+
         // draw log record windows
         let record_wnds = self.record_windows.take().unwrap();
         let mut keep_wnds = BTreeMap::new();
@@ -161,6 +570,16 @@ impl DebugConsole {
                                                 ui.label(format!("{}", &wnd.record.line));
                                             });
                                         });
+                                        for (key, value) in &wnd.record.fields {
+                                            body.row(ROW_HEIGHT, |mut row| {
+                                                row.col(|ui| {
+                                                    ui.label(key);
+                                                });
+                                                row.col(|ui| {
+                                                    ui.label(value);
+                                                });
+                                            });
+                                        }
                                     });
                             });
 
@@ -187,7 +606,7 @@ impl DebugConsole {
                                                     0.0,
                                                     TextFormat::simple(
                                                         FontId::monospace(12.0),
-                                                        TEXT_COLOUR,
+                                                        theme.text,
                                                     ),
                                                 );
                                                 ui.fonts(|f| f.layout_job(lj))
@@ -214,11 +633,15 @@ impl DebugConsole {
         self.record_windows = Some(keep_wnds);
 
         // draw debug windows
-        for (_, (is_open, wnd)) in &mut self.debug_windows {
+        // titled by their registered name, which is unique, so it doubles as the egui id
+        for (id, (is_open, wnd)) in &mut self.debug_windows {
             if *is_open {
-                egui::Window::new("aaaaaa").show(ui.ctx(), |ui| {
-                    wnd.draw(ui);
-                });
+                egui::Window::new(id.as_str())
+                    .id(egui::Id::new(("k9_debug_window", id.as_str())))
+                    .open(is_open)
+                    .show(ui.ctx(), |ui| {
+                        wnd.draw(ui);
+                    });
             }
         }
 
@@ -306,6 +729,13 @@ impl DebugConsole {
                         }
 
                         let te_resp = te_output.response;
+                        if self.console_cursor_to_end {
+                            self.console_cursor_to_end = false;
+                            let mut state = te_output.state;
+                            let end = CCursor::new(self.console_text.chars().count());
+                            state.set_ccursor_range(Some(CCursorRange::one(end)));
+                            state.store(ui.ctx(), te_resp.id);
+                        }
                         if self.set_console_focus {
                             self.set_console_focus = false;
                             te_resp.request_focus();
@@ -400,6 +830,12 @@ impl DebugConsole {
                                         cmds_text.pop();
                                         cmds_text_full.pop();
                                     }
+
+                                    if self.preview_autocomplete_total > self.preview_autocomplete_cmds.len() {
+                                        let msg = format!("\n(showing {} of {})", self.preview_autocomplete_cmds.len(), self.preview_autocomplete_total);
+                                        cmds_text += &msg;
+                                        cmds_text_full += &msg;
+                                    }
                                 }
 
                                 let draw_pos = te_output.text_draw_pos.to_vec2();
@@ -414,7 +850,7 @@ impl DebugConsole {
                                 let galley = painter_tmp.layout(
                                     cmds_text_full,
                                     FontId::monospace(12.0),
-                                    TEXT_COLOUR,
+                                    theme.text,
                                     f32::INFINITY,
                                 );
 
@@ -426,16 +862,16 @@ impl DebugConsole {
                                 painter.set_layer_id(egui::LayerId::debug());
 
                                 let fill = Color32::from_rgba_unmultiplied(
-                                    OFF_BG_COLOUR.r(),
-                                    OFF_BG_COLOUR.g(),
-                                    OFF_BG_COLOUR.b(),
+                                    theme.off_bg.r(),
+                                    theme.off_bg.g(),
+                                    theme.off_bg.b(),
                                     (ui_opacity * 255.0) as u8,
                                 );
                                 painter.rect(
                                     background_rect,
                                     0.0,
                                     fill,
-                                    egui::Stroke::new(2.0, OFF_ACCENT_COLOUR),
+                                    egui::Stroke::new(2.0, theme.off_accent),
                                 );
 
                                 painter.text(
@@ -443,7 +879,7 @@ impl DebugConsole {
                                     egui::Align2::LEFT_BOTTOM,
                                     cmds_text,
                                     FontId::monospace(12.0),
-                                    TEXT_COLOUR,
+                                    theme.text,
                                 );
 
                                 let active_preview_rect = galley.pos_from_cursor(&galley.from_rcursor(RCursor { column: 0, row: active_text.1 }));
@@ -454,15 +890,16 @@ impl DebugConsole {
                                     egui::Align2::LEFT_BOTTOM,
                                     active_text.0,
                                     FontId::monospace(12.0),
-                                    OFF_ACCENT_COLOUR,
+                                    theme.off_accent,
                                 );
                             }
                         }
 
-                        // draw autocomplete
+                        // draw autocomplete, only the rest of a prefix match can be drawn after the typed text
                         if let Some((preview_txt, _)) = &self.selected_autocomplete_cmd {
                             let input_len = self.console_text.len();
-                            if input_len < preview_txt.len() {
+                            let case_insensitive = *self.case_insensitive.lock().unwrap();
+                            if input_len < preview_txt.len() && command_starts_with(preview_txt, &self.console_text, case_insensitive) {
                                 let render_text = &preview_txt[self.console_text.len()..];
                                 let draw_pos = te_output.text_draw_pos.to_vec2();
                                 let draw_pos = te_output.galley.rect.max + draw_pos;
@@ -472,31 +909,48 @@ impl DebugConsole {
                                     egui::Align2::LEFT_BOTTOM,
                                     render_text,
                                     FontId::monospace(12.0),
-                                    DIM_TEXT_COLOUR,
+                                    theme.dim_text,
                                 );
                             }
                         }
 
                         // autocomplete logic
                         if te_resp.changed() {
+                            // editing a recalled command makes it the user's own text again
+                            self.history_cursor = None;
+
                             let prev_selected = self.selected_autocomplete_cmd.take();
                             self.preview_autocomplete_cmds.clear();
+                            self.preview_autocomplete_total = 0;
 
-                            if !self.console_text.is_empty() {
+                            // only the command token is completed, once whitespace is typed we're into the arguments
+                            let past_command_token = self.console_text.contains(char::is_whitespace);
+                            if !self.console_text.is_empty() && !past_command_token {
                                 // gather predictions
-                                let mut prev_index = None;
-                                let mut it = 0;
-                                for cmd in self.console_commands.iter() {
-                                    if cmd.0.starts_with(&self.console_text) {
-                                        self.preview_autocomplete_cmds.push(cmd.0.clone());
-                                        if let Some((name, _)) = &prev_selected {
-                                            if *cmd.0 == *name {
-                                                prev_index = Some(it);
-                                            }
-                                        }
-                                        it += 1;
-                                    }
-                                }
+                                let limit = *self.autocomplete_limit.lock().unwrap() as usize;
+                                let case_insensitive = *self.case_insensitive.lock().unwrap();
+                                let fuzzy = *self.fuzzy_autocomplete.lock().unwrap();
+                                // an alias match suggests the command it belongs to
+                                let mut scored: Vec<(i64, &String)> = self
+                                    .console_commands
+                                    .iter()
+                                    .filter_map(|(name, cmd)| {
+                                        let score = std::iter::once(name)
+                                            .chain(cmd.aliases.iter())
+                                            .chain(self.user_aliases_of(name))
+                                            .filter_map(|x| autocomplete_score(x, &self.console_text, case_insensitive, fuzzy))
+                                            .max()?;
+                                        Some((score, name))
+                                    })
+                                    .collect();
+                                // stable, so equal scores stay in name order
+                                scored.sort_by_key(|x| std::cmp::Reverse(x.0));
+
+                                self.preview_autocomplete_total = scored.len();
+                                self.preview_autocomplete_cmds.extend(scored.into_iter().take(limit).map(|(_, name)| name.clone()));
+                                let prev_index = prev_selected.as_ref().and_then(|(name, _)| {
+                                    self.preview_autocomplete_cmds.iter().position(|x| x == name)
+                                });
 
                                 if let Some(idx) = prev_index {
                                     self.selected_autocomplete_cmd = Some((self.preview_autocomplete_cmds[idx].clone(), idx));
@@ -510,18 +964,33 @@ impl DebugConsole {
 
                         // handle sending command
                         if te_resp.lost_focus() {
+                            let enter_runs_autocomplete = *self.enter_runs_autocomplete.lock().unwrap();
+                            let case_insensitive = *self.case_insensitive.lock().unwrap();
+                            let fuzzy = *self.fuzzy_autocomplete.lock().unwrap();
                             ui.input(|input| {
                                 if input.key_pressed(egui::Key::Enter) {
-                                    log::info!("Execute: {}", self.console_text.trim());
+                                    // complete to the selected suggestion first so a partially typed command isn't run
+                                    if let Some((cmd_text, _)) = &self.selected_autocomplete_cmd {
+                                        if autocomplete_score(cmd_text, &self.console_text, case_insensitive, fuzzy).is_some() && *cmd_text != self.console_text {
+                                            self.console_text = cmd_text.clone();
+                                            self.preview_autocomplete_cmds.clear();
+                                            self.draw_preview_commands_list = false;
 
-                                    match self.parse_command() {
-                                        Ok((cmd, args))
-                                            => match (self.console_commands.get_mut(&cmd).unwrap().cb)(ConsoleCommandInterface { debug_windows: &mut self.debug_windows }, args) {
-                                            Ok(()) => {},
-                                            Err(e) => log::error!("{e}"),
+                                            if !enter_runs_autocomplete {
+                                                self.set_console_focus = true;
+                                                return;
+                                            }
                                         }
-                                        Err(e) => log::error!("command error: {e:?}"),
                                     }
+
+                                    log::info!("Execute: {}", self.console_text.trim());
+
+                                    let cmd_text = self.console_text.trim().to_owned();
+                                    self.push_history(cmd_text);
+                                    self.history_cursor = None;
+
+                                    let line = std::mem::take(&mut self.console_text);
+                                    self.execute(&line);
                                     self.console_text.clear();
                                     self.set_console_focus = true;
                                     self.selected_autocomplete_cmd = None;
@@ -534,34 +1003,75 @@ impl DebugConsole {
 
                         // handle up/down key navigation logic, includes autocomplete logic and history logic
                         if self.console_has_focus {
+                            let wrap = *self.autocomplete_wrap.lock().unwrap();
                             ui.input_mut(|input| {
                                 if input.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp) {
                                     if let Some((_, it)) = &self.selected_autocomplete_cmd {
                                         if *it == 0 {
-                                            let len = self.preview_autocomplete_cmds.len();
-                                            self.selected_autocomplete_cmd = Some((self.preview_autocomplete_cmds[len - 1].clone(), len - 1));
+                                            if wrap {
+                                                let len = self.preview_autocomplete_cmds.len();
+                                                self.selected_autocomplete_cmd = Some((self.preview_autocomplete_cmds[len - 1].clone(), len - 1));
+                                            }
                                         } else {
                                             self.selected_autocomplete_cmd = Some((self.preview_autocomplete_cmds[it - 1].clone(), it - 1));
                                         }
-                                    } else {
-                                        // todo: history
+                                    } else if !self.history.is_empty() {
+                                        let idx = match self.history_cursor {
+                                            Some(idx) => idx.saturating_sub(1),
+                                            None => self.history.len() - 1,
+                                        };
+                                        self.history_cursor = Some(idx);
+                                        self.console_text = self.history[idx].clone();
+                                        self.console_cursor_to_end = true;
                                     }
                                 }
                                 if input.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown) {
                                     if let Some((_, it)) = &self.selected_autocomplete_cmd {
                                         if *it as i32 == self.preview_autocomplete_cmds.len() as i32 - 1 { // cast to handle underflow
-                                            self.selected_autocomplete_cmd = Some((self.preview_autocomplete_cmds[0].clone(), 0));
+                                            if wrap {
+                                                self.selected_autocomplete_cmd = Some((self.preview_autocomplete_cmds[0].clone(), 0));
+                                            }
                                         } else {
                                             self.selected_autocomplete_cmd = Some((self.preview_autocomplete_cmds[it + 1].clone(), it + 1));
                                         }
-                                    } else {
-                                        // todo: history
+                                    } else if let Some(idx) = self.history_cursor {
+                                        // walking past the newest entry gives an empty entry back
+                                        if idx + 1 < self.history.len() {
+                                            self.history_cursor = Some(idx + 1);
+                                            self.console_text = self.history[idx + 1].clone();
+                                        } else {
+                                            self.history_cursor = None;
+                                            self.console_text.clear();
+                                        }
+                                        self.console_cursor_to_end = true;
                                     }
                                 }
                             });
                         }
                     });
 
+                // command output, sits between the log and the entry
+                if !self.output.is_empty() {
+                    egui::TopBottomPanel::bottom("k9_console_output_panel")
+                        .frame(egui::Frame::none())
+                        .resizable(true)
+                        .default_height(80.0)
+                        .show_inside(ui, |ui| {
+                            egui::ScrollArea::vertical()
+                                .stick_to_bottom(true)
+                                .auto_shrink([false, false])
+                                .show(ui, |ui| {
+                                    for line in &self.output {
+                                        let colour = if line.is_error { self.level_theme.error.text } else { theme.text };
+                                        ui.horizontal(|ui| {
+                                            ui.label(RichText::new(">").monospace().color(theme.off_accent));
+                                            ui.label(RichText::new(&line.text).monospace().color(colour));
+                                        });
+                                    }
+                                });
+                        });
+                }
+
                 egui::CentralPanel::default()
                     .frame(egui::Frame::none())
                     .show_inside(ui, |ui| {
@@ -570,7 +1080,64 @@ impl DebugConsole {
 
                         ui.set_clip_rect(ui.available_rect_before_wrap());
 
-                        egui_extras::TableBuilder::new(ui)
+                        // rows are the indices of the records shown, records before a clear and
+                        // those with a level toggled off are left out
+                        let records = logger.read().unwrap();
+                        let mut hidden = BTreeMap::new();
+                        let rows: Vec<usize> = (self.log_cleared_before..records.len())
+                            .filter(|idx| {
+                                let level = records[*idx].level;
+                                let shown = self.log_level_filter.get(&level).copied().unwrap_or(true);
+                                if !shown {
+                                    *hidden.entry(level).or_insert(0) += 1;
+                                }
+                                shown
+                            })
+                            .collect();
+
+                        // level toggles, an off level shows how many records it's hiding
+                        ui.horizontal(|ui| {
+                            for level in LOG_LEVELS {
+                                let letter = &level.as_str()[..1];
+                                let shown = self.log_level_filter.entry(level).or_insert(true);
+                                let label = if *shown {
+                                    format!("[{letter}]")
+                                } else {
+                                    format!("[{letter}:{}]", hidden.get(&level).unwrap_or(&0))
+                                };
+                                let text = RichText::new(label)
+                                    .monospace()
+                                    .color(self.level_theme.get(level).text);
+                                ui.toggle_value(shown, text)
+                                    .on_hover_text(format!("show {} records", level.as_str().to_lowercase()));
+                            }
+                        });
+
+                        // F3/Shift+F3 jump to the next/previous warning or error
+                        let jump_forward = ui.input_mut(|input| {
+                            if input.consume_key(egui::Modifiers::SHIFT, egui::Key::F3) {
+                                Some(false)
+                            } else if input.consume_key(egui::Modifiers::NONE, egui::Key::F3) {
+                                Some(true)
+                            } else {
+                                None
+                            }
+                        });
+                        let mut scroll_to = None;
+                        // the cursor is a record index, the jump works in rows
+                        if let Some(forward) = jump_forward {
+                            let from = rows.iter().position(|x| Some(*x) == self.log_cursor);
+                            if let Some(row) = find_log_issue(&records, &rows, from, forward) {
+                                self.log_cursor = Some(rows[row]);
+                                scroll_to = Some(row);
+                            }
+                        }
+
+                        let mut table = egui_extras::TableBuilder::new(ui);
+                        if let Some(idx) = scroll_to {
+                            table = table.scroll_to_row(idx, Some(Align::Center));
+                        }
+                        table
                             .stick_to_bottom(true)
                             .column(Column::exact(main_width))
                             .column(Column::exact(TIMESTAMP_WIDTH))
@@ -578,10 +1145,10 @@ impl DebugConsole {
                             .min_scrolled_height(60.0)
                             .body(|body| {
                                 const ROW_HEIGHT: f32 = 18.0;
-                                let records = logger.read().unwrap();
-                                let num_rows = records.len();
+                                let num_rows = rows.len();
 
-                                body.rows(ROW_HEIGHT, num_rows, |idx, mut row| {
+                                body.rows(ROW_HEIGHT, num_rows, |row_idx, mut row| {
+                                    let idx = rows[row_idx];
                                     let record = &records[idx];
                                     row.col(|ui| {
                                         // draw warn/error background bar
@@ -594,22 +1161,22 @@ impl DebugConsole {
                                             (ui_opacity * 255.0 * 0.5) as u8
                                         };
 
-                                        match &record.level {
-                                            log::Level::Error => painter.rect_filled(
+                                        let level_colours = self.level_theme.get(record.level);
+                                        if let Some(bar) = level_colours.bar {
+                                            painter.rect_filled(
                                                 avail,
                                                 0.0,
                                                 Color32::from_rgba_unmultiplied(
-                                                    64, 8, 8, bar_opacity,
+                                                    bar.r(), bar.g(), bar.b(), bar_opacity,
                                                 ),
-                                            ),
-                                            log::Level::Warn => painter.rect_filled(
-                                                    avail,
+                                            );
+                                        }
+                                        if self.log_cursor == Some(idx) {
+                                            painter.rect_stroke(
+                                                avail,
                                                 0.0,
-                                                Color32::from_rgba_unmultiplied(
-                                                    64, 64, 0, bar_opacity,
-                                                ),
-                                            ),
-                                            _ => {}
+                                                egui::Stroke::new(1.0, theme.off_accent),
+                                            );
                                         }
 
                                         let mut job = LayoutJob::default();
@@ -619,39 +1186,23 @@ impl DebugConsole {
                                         };
 
                                         let mut format = TextFormat::default();
-                                        format.color = TEXT_COLOUR;
+                                        format.color = theme.text;
                                         format.valign = Align::BOTTOM;
                                         format.font_id = FontId::monospace(14.0);
 
                                         job.append("[", 0.0, format.clone());
 
-                                        format.color = DIM_TEXT_COLOUR;
+                                        format.color = theme.dim_text;
                                         job.append(
                                             &format!("{}", &record.idx),
                                             0.0,
                                             format.clone(),
                                         );
 
-                                        format.color = TEXT_COLOUR;
+                                        format.color = theme.text;
                                         job.append(":", 0.0, format.clone());
 
-                                        match &record.level {
-                                            log::Level::Debug => {
-                                                format.color = Color32::GOLD
-                                            }
-                                            log::Level::Error => {
-                                                format.color = Color32::LIGHT_RED
-                                            }
-                                            log::Level::Warn => {
-                                                format.color = Color32::LIGHT_YELLOW
-                                            }
-                                            log::Level::Info => {
-                                                format.color = Color32::LIGHT_GREEN
-                                            }
-                                            log::Level::Trace => {
-                                                format.color = Color32::LIGHT_BLUE
-                                            }
-                                        }
+                                        format.color = level_colours.text;
 
                                         job.append(
                                             &format!("{}", &record.level),
@@ -659,21 +1210,30 @@ impl DebugConsole {
                                             format.clone(),
                                         );
 
-                                        format.color = TEXT_COLOUR;
+                                        format.color = theme.text;
                                         job.append("] ", 0.0, format.clone());
 
-                                        format.color = DIM_TEXT_COLOUR;
+                                        format.color = theme.dim_text;
                                         format.italics = true;
 
                                         job.append(&record.debug_text, 0.0, format);
 
-                                        if ui
+                                        let resp = ui
                                             .add(
                                                 egui::Label::new(job).sense(Sense::click()),
                                             )
-                                            .on_hover_cursor(egui::CursorIcon::PointingHand)
-                                            .clicked()
-                                        {
+                                            .on_hover_cursor(egui::CursorIcon::PointingHand);
+
+                                        // records naming a command fill the entry with it instead
+                                        if let Some(cmd) = record.fields.get(CONSOLE_COMMAND_FIELD) {
+                                            if resp.on_hover_text(format!("use '{cmd}'")).clicked() {
+                                                self.console_text = format!("{cmd} ");
+                                                self.console_cursor_to_end = true;
+                                                self.set_console_focus = true;
+                                                self.selected_autocomplete_cmd = None;
+                                                self.draw_preview_commands_list = false;
+                                            }
+                                        } else if resp.clicked() {
                                             let fake_text = records[idx].text.clone();
                                             self.record_windows.as_mut().unwrap().insert(
                                                 idx,
@@ -689,7 +1249,7 @@ impl DebugConsole {
                                     row.col(|ui| {
                                         let time = record.local_time;
                                         ui.label(RichText::new(format!("{:02}:{:02}:{:02}",
-                                        time.hour(), time.minute(), time.second())).color(OFF_ACCENT_COLOUR));
+                                        time.hour(), time.minute(), time.second())).color(theme.off_accent));
                                     });
                                 });
                             });
@@ -699,10 +1259,240 @@ impl DebugConsole {
             });
     }
 
-    fn parse_command(&self) -> Result<(String, BTreeMap<String, CallbackArgumentValue>), ParseCommandErr> {
+    // repeats of the last command aren't recorded, like bash's ignoredups
+    fn push_history(&mut self, cmd_text: String) {
+        if cmd_text.is_empty() || self.history.last() == Some(&cmd_text) {
+            return;
+        }
+
+        if let Some(path) = &self.history_path {
+            let res = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .and_then(|mut file| writeln!(file, "{cmd_text}"));
+            if let Err(e) = res {
+                log::warn!("couldn't append to console history {path:?}: {e}");
+            }
+        }
+
+        if self.history.len() == HISTORY_LIMIT {
+            self.history.remove(0);
+        }
+        self.history.push(cmd_text);
+    }
+
+    // runs each ';' separated command in the line in order, see split_command_line
+    pub fn execute(&mut self, line: &str) {
+        let stop_on_error = *self.chain_stops_on_error.lock().unwrap();
+
+        let commands = split_command_line(line);
+        let total = commands.len();
+        for (i, text) in commands.into_iter().enumerate() {
+            let ok = match self.parse_command(&text) {
+                Ok((cmd, args)) => {
+                    // callbacks only queue changes, they're applied once the callback has returned
+                    let mut actions = Vec::new();
+                    let ccf = ConsoleCommandInterface { command: &cmd, debug_windows: &self.debug_windows, actions: &mut actions };
+                    let res = (self.console_commands.get_mut(&cmd).unwrap().cb)(ccf, args);
+                    self.apply_console_actions(actions);
+                    match res {
+                        Ok(()) => true,
+                        Err(e) => {
+                            log::error!("{e}");
+                            self.push_output(&e, true);
+                            false
+                        },
+                    }
+                }
+                Err(e) => {
+                    let msg = format!("command error: {e:?}");
+                    log::error!("{msg}");
+                    self.push_output(&msg, true);
+                    false
+                },
+            };
+
+            if !ok && stop_on_error && i + 1 < total {
+                log::warn!("skipped {} remaining command(s) after '{text}' failed.", total - i - 1);
+                break;
+            }
+        }
+    }
+
+    // multi-line text is split so each line gets its own prefix
+    fn push_output(&mut self, text: &str, is_error: bool) {
+        for line in text.lines() {
+            if self.output.len() == OUTPUT_LIMIT {
+                self.output.remove(0);
+            }
+            self.output.push(ConsoleOutputLine { text: line.to_owned(), is_error });
+        }
+    }
+
+    // help runs inside a command callback which can't see the command map, so the text is built
+    // here once the callback has returned
+    // the command list is logged rather than printed so each line can be clicked to put its command
+    // into the entry
+    fn log_command_list(&self) {
+        let width = self.console_commands.keys().map(|x| x.len()).max().unwrap_or(0);
+        for (name, cmd) in &self.console_commands {
+            log::info!(console_command = name.as_str(); "{name:width$}  {}", cmd.description);
+        }
+    }
+
+    fn command_help(&self, name: &str) -> Result<String, String> {
+        let name = self
+            .resolve_command_name(name)
+            .ok_or(format!("no command named '{name}'"))?;
+        let cmd = &self.console_commands[&name];
+
+        let mut text = format!("{name}: {}", cmd.description);
+        let aliases: Vec<&String> = cmd.aliases.iter().chain(self.user_aliases_of(&name)).collect();
+        if !aliases.is_empty() {
+            let aliases: Vec<&str> = aliases.into_iter().map(String::as_str).collect();
+            text += &format!("\n  aliases: {}", aliases.join(", "));
+        }
+        for arg in &cmd.args {
+            text += &match arg.cba_type {
+                CallbackArgumentType::Flag => format!("\n  --{}", arg.name),
+                _ => format!("\n  {}: {:?}", arg.name, arg.cba_type),
+            };
+            if arg.optional {
+                text += " (optional)";
+            }
+            if let Some(description) = &arg.description {
+                text += &format!(" - {description}");
+            }
+        }
+        Ok(text)
+    }
+
+    fn apply_console_actions(&mut self, actions: Vec<ConsoleAction>) {
+        for action in actions {
+            match action {
+                ConsoleAction::Println(text) => self.push_output(&text, false),
+                ConsoleAction::SetAlias(name, target) => match self.set_user_alias(&name, &target) {
+                    Ok(command) => self.push_output(&format!("'{name}' now runs '{command}'"), false),
+                    Err(e) => self.push_output(&e, true),
+                },
+                ConsoleAction::RemoveAlias(name) => {
+                    if self.user_aliases.remove(&name).is_none() {
+                        self.push_output(&format!("no alias named '{name}'"), true);
+                    }
+                }
+                ConsoleAction::SetLogFilter(levels) => {
+                    self.log_level_filter = levels;
+                    let shown: Vec<&str> = LOG_LEVELS
+                        .into_iter()
+                        .filter(|x| self.log_level_filter[x])
+                        .map(|x| x.as_str())
+                        .collect();
+                    self.push_output(&format!("log showing: {}", shown.join(", ")), false);
+                }
+                ConsoleAction::Clear => {
+                    self.output.clear();
+                    self.clear_log = true;
+                }
+                ConsoleAction::PrintHelp(None) => self.log_command_list(),
+                ConsoleAction::PrintHelp(Some(command)) => match self.command_help(&command) {
+                    Ok(text) => self.push_output(&text, false),
+                    Err(e) => self.push_output(&e, true),
+                },
+                ConsoleAction::SetDebugWindowOpen(id, set_open) => {
+                    if let Some((is_open, _)) = self.debug_windows.get_mut(&id) {
+                        *is_open = set_open;
+                    }
+                }
+                ConsoleAction::RegisterCommand(name, cmd) => {
+                    if self.console_commands.insert(name.clone(), cmd).is_some() {
+                        log::warn!("console command '{name}' was overwritten.");
+                    }
+                    self.command_aliases = build_alias_map(&self.console_commands);
+                }
+                ConsoleAction::AddDebugWindow(id, mut wnd) => {
+                    if let Some(state) = self.pending_window_states.remove(&id) {
+                        wnd.restore_state(&state);
+                    }
+                    if self.debug_windows.insert(id.clone(), (false, wnd)).is_some() {
+                        log::warn!("debug window '{id}' was overwritten.");
+                    }
+                }
+            }
+        }
+    }
+
+    // the registered name for a typed command or alias, an exact match wins over one that only
+    // differs in case
+    fn resolve_command_name(&self, typed: &str) -> Option<String> {
+        if self.console_commands.contains_key(typed) {
+            return Some(typed.to_owned());
+        }
+        if let Some(name) = self.command_aliases.get(typed) {
+            return Some(name.clone());
+        }
+        if self.user_aliases.contains_key(typed) {
+            return self.resolve_user_alias(typed);
+        }
+        if !*self.case_insensitive.lock().unwrap() {
+            return None;
+        }
+        let typed = typed.to_lowercase();
+        self.console_commands
+            .keys()
+            .find(|name| name.to_lowercase() == typed)
+            .or_else(|| {
+                self.command_aliases
+                    .iter()
+                    .find(|(alias, _)| alias.to_lowercase() == typed)
+                    .map(|(_, name)| name)
+            })
+            .cloned()
+            .or_else(|| {
+                let alias = self.user_aliases.keys().find(|alias| alias.to_lowercase() == typed)?;
+                self.resolve_user_alias(alias)
+            })
+    }
+
+    // the command a user alias runs, aliases are stored already resolved by set_user_alias
+    fn resolve_user_alias(&self, alias: &str) -> Option<String> {
+        self.user_aliases
+            .get(alias)
+            .filter(|x| self.console_commands.contains_key(*x))
+            .cloned()
+    }
+
+    // user aliases that end at the command, for autocomplete and help
+    fn user_aliases_of<'a>(&'a self, command: &'a str) -> impl Iterator<Item = &'a String> + 'a {
+        self.user_aliases
+            .keys()
+            .filter(move |alias| self.resolve_user_alias(alias).as_deref() == Some(command))
+    }
+
+    // an alias can't take a command's name or a registered alias. it stores the command the target
+    // resolves to rather than the target as typed, so it still works when the target was typed in
+    // a different case and can't form a loop. returns that command.
+    fn set_user_alias(&mut self, name: &str, target: &str) -> Result<String, String> {
+        let valid_name = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid_name {
+            return Err(format!("'{name}' isn't a valid command name, use letters, digits and '_'"));
+        }
+        if self.console_commands.contains_key(name) || self.command_aliases.contains_key(name) {
+            return Err(format!("'{name}' is already a command, an alias can't replace it"));
+        }
+
+        let command = self
+            .resolve_command_name(target)
+            .ok_or(format!("'{target}' isn't a command or an alias"))?;
+        self.user_aliases.insert(name.to_owned(), command.clone());
+        Ok(command)
+    }
+
+    fn parse_command(&self, text: &str) -> Result<(String, BTreeMap<String, CallbackArgumentValue>), ParseCommandErr> {
         let debug_log = *self.debug_console_commands.lock().unwrap();
 
-        let text = self.console_text.trim().to_owned();
+        let text = text.trim();
 
         let parse_tree = {
             if debug_log {
@@ -742,6 +1532,8 @@ impl DebugConsole {
                 nodes.next().unwrap(),
             );
 
+            let command = self.resolve_command_name(&command).unwrap_or(command);
+
             if debug_log {
                 log::trace!("Parsed Command: {command}");
             }
@@ -757,13 +1549,24 @@ impl DebugConsole {
                 if let Some(cmd) = self.console_commands.get(&command) {
                     // collect named args, indexed args, and flags*
                     // *flags are actually just named values set to true
-                    let mut named_args = BTreeMap::new();
+                    let mut named_args: BTreeMap<String, CommandValue> = BTreeMap::new();
                     let mut indexed_vals = VecDeque::new();
+                    // a list argument set by name keeps taking the values after it, e.g. `ids: 3 7 9`
+                    let mut open_list: Option<String> = None;
                     for arg in args {
                         if arg.0.is_empty() {
-                            indexed_vals.push_back(arg.1);
+                            if let Some(list) = open_list.as_ref().and_then(|x| named_args.get_mut(x)) {
+                                list.elements.extend(arg.1.elements);
+                            } else {
+                                indexed_vals.push_back(arg.1);
+                            }
                         } else {
                             let name = arg.0.clone();
+                            open_list = cmd
+                                .args
+                                .iter()
+                                .find(|x| x.name == name && matches!(x.cba_type, CallbackArgumentType::List(_)))
+                                .map(|x| x.name.clone());
                             if named_args.insert(arg.0, arg.1).is_some() {
                                 return Err(ParseCommandErr::DuplicateCommand(name));
                             }
@@ -802,13 +1605,19 @@ impl DebugConsole {
                     let mut missed_defs = missed_mandatory;
                     missed_defs.append(&mut missed_optional);
 
-                    let indexed_len = indexed_vals.len();
-
-                    if indexed_len >= mandatory_len {
-                        for _ in 0..indexed_len {
-                            let indexed_val = indexed_vals.pop_front().unwrap();
+                    if indexed_vals.len() >= mandatory_len {
+                        while let Some(mut indexed_val) = indexed_vals.pop_front() {
                             let missed_def = missed_defs.pop_front().unwrap();
 
+                            // a list filled in order takes the values after it too, leaving one for
+                            // each required argument that's still to come
+                            if let CallbackArgumentType::List(_) = missed_def.cba_type {
+                                let required_after = missed_defs.iter().filter(|x| !x.optional).count();
+                                while indexed_vals.len() > required_after {
+                                    indexed_val.elements.extend(indexed_vals.pop_front().unwrap().elements);
+                                }
+                            }
+
                             let arg_value = parse_value_via_definition(&indexed_val, missed_def);
                             match arg_value {
                                 Ok(x) => { complete_args.insert(missed_def.name.clone(), x); },
@@ -844,7 +1653,7 @@ impl DebugConsole {
                 }
             }
         } else {
-            return Err(ParseCommandErr::Invalid(text));
+            return Err(ParseCommandErr::Invalid(text.to_owned()));
         }
     }
 }
@@ -870,6 +1679,9 @@ pub struct ConsoleCommand {
     >,
     args: Vec<CallbackArgumentDefinition>,
     description: String,
+    // other names the command can be run by, they show in help and autocomplete suggests the
+    // command when one matches
+    aliases: Vec<String>,
 }
 
 #[derive(Debug)]
@@ -877,6 +1689,7 @@ pub struct CallbackArgumentDefinition {
     pub name: String,
     pub cba_type: CallbackArgumentType,
     pub optional: bool,
+    pub description: Option<String>,
 }
 
 #[derive(Debug)]
@@ -885,9 +1698,14 @@ pub enum CallbackArgumentType {
     Float64,
     Int32,
     Int64,
+    UInt32,
+    UInt64,
+    USize,
     String,
     Bool,
     Flag,
+    // any number of values of the element type
+    List(Box<CallbackArgumentType>),
 }
 
 #[derive(Debug)]
@@ -896,9 +1714,20 @@ pub enum CallbackArgumentValue {
     Float64(f64),
     Int32(i32),
     Int64(i64),
+    UInt32(u32),
+    UInt64(u64),
+    USize(usize),
     String(String),
     Bool(bool),
     Flag(bool),
+    List(Vec<CallbackArgumentValue>),
+}
+
+// an argument's value as typed, list arguments use the elements and everything else the text
+#[derive(Debug)]
+struct CommandValue {
+    text: String,
+    elements: Vec<String>,
 }
 
 impl ConsoleCommand {
@@ -910,66 +1739,348 @@ impl ConsoleCommand {
             + 'static,
         args: Vec<CallbackArgumentDefinition>,
         description: String,
+    ) -> Self {
+        Self::with_aliases(cb, args, description, Vec::new())
+    }
+    pub fn with_aliases(
+        cb: impl FnMut(
+                ConsoleCommandInterface,
+                BTreeMap<String, CallbackArgumentValue>,
+            ) -> Result<(), String>
+            + 'static,
+        args: Vec<CallbackArgumentDefinition>,
+        description: String,
+        aliases: Vec<String>,
     ) -> Self {
         Self {
             cb: Box::new(cb),
             args,
             description,
+            aliases,
         }
     }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+    pub fn args(&self) -> &[CallbackArgumentDefinition] {
+        &self.args
+    }
+    pub fn aliases(&self) -> &[String] {
+        &self.aliases
+    }
 }
 
 pub struct ConsoleCommandInterface<'a> {
-    debug_windows: &'a mut BTreeMap<String, (bool, Box<dyn DebugUiWindow>)>,
+    command: &'a str,
+    debug_windows: &'a BTreeMap<String, (bool, Box<dyn DebugUiWindow>)>,
+    actions: &'a mut Vec<ConsoleAction>,
 }
 impl<'a> ConsoleCommandInterface<'a> {
     pub fn open_debug_window(&mut self, id: &String) -> bool {
-        if let Some((is_open, _)) = self.debug_windows.get_mut(id) {
-            *is_open = true;
-            true
-        } else {
-            false
-        }
+        self.set_open_debug_window(id, true)
     }
     pub fn close_debug_window(&mut self, id: &String) -> bool {
-        if let Some((is_open, _)) = self.debug_windows.get_mut(id) {
-            *is_open = false;
-            true
-        } else {
-            false
-        }
+        self.set_open_debug_window(id, false)
     }
     pub fn set_open_debug_window(&mut self, id: &String, set_open: bool) -> bool {
-        if let Some((is_open, _)) = self.debug_windows.get_mut(id) {
-            *is_open = set_open;
+        if self.has_debug_window(id) {
+            self.actions
+                .push(ConsoleAction::SetDebugWindowOpen(id.clone(), set_open));
             true
         } else {
             false
         }
     }
+    // shown in the console's output area, not the log
+    pub fn println(&mut self, text: impl Into<String>) {
+        self.actions.push(ConsoleAction::Println(text.into()));
+    }
+    // logged straight away with the command's name as the target
+    pub fn log(&self, level: log::Level, msg: impl std::fmt::Display) {
+        log::log!(target: self.command, level, "{msg}");
+    }
+    pub fn warn(&self, msg: impl std::fmt::Display) {
+        self.log(log::Level::Warn, msg);
+    }
+    pub fn register_command(&mut self, name: impl ToString, cmd: ConsoleCommand) {
+        self.actions
+            .push(ConsoleAction::RegisterCommand(name.to_string(), cmd));
+    }
+    pub fn add_debug_window(&mut self, id: impl ToString, wnd: Box<dyn DebugUiWindow>) {
+        self.actions
+            .push(ConsoleAction::AddDebugWindow(id.to_string(), wnd));
+    }
+
+    // windows added earlier in the same callback count as existing
+    fn has_debug_window(&self, id: &String) -> bool {
+        self.debug_windows.contains_key(id)
+            || self.actions.iter().any(|a| match a {
+                ConsoleAction::AddDebugWindow(added_id, _) => added_id == id,
+                _ => false,
+            })
+    }
+}
+
+// changes requested by a command callback, applied by the console after the callback returns
+enum ConsoleAction {
+    Println(String),
+    PrintHelp(Option<String>),
+    Clear,
+    SetLogFilter(BTreeMap<log::Level, bool>),
+    SetAlias(String, String),
+    RemoveAlias(String),
+    SetDebugWindowOpen(String, bool),
+    RegisterCommand(String, ConsoleCommand),
+    AddDebugWindow(String, Box<dyn DebugUiWindow>),
+}
+
+struct ConsoleOutputLine {
+    text: String,
+    is_error: bool,
 }
 
+// row of the next warn/error record after from (or the previous one before it), wrapping around
+// the log. rows are indices into records, from is None when nothing has been jumped to yet.
+fn find_log_issue(
+    records: &[DebugLogRecord],
+    rows: &[usize],
+    from: Option<usize>,
+    forward: bool,
+) -> Option<usize> {
+    let len = rows.len();
+    if len == 0 {
+        return None;
+    }
+
+    let start = match (from, forward) {
+        (Some(idx), true) => idx + 1,
+        (Some(idx), false) => idx + len - 1,
+        (None, true) => 0,
+        (None, false) => len - 1,
+    };
+    (0..len)
+        .map(|offset| {
+            if forward {
+                (start + offset) % len
+            } else {
+                (start + len - offset) % len
+            }
+        })
+        .find(|row| records[rows[*row]].level <= log::Level::Warn)
+}
+
+// ui is the contents of the window k9 opens for it. the whole egui context is available through
+// ui.ctx() for anything that lives outside that window, e.g. extra egui::Windows, screen anchored
+// tooltips via egui::show_tooltip_at or global input state via ctx.input(..).
 pub trait DebugUiWindow {
     fn draw(&mut self, ui: &mut egui::Ui);
+
+    // saved on exit under the window's name when CreationArgs::debug_ui_state_path is set, and
+    // handed back to restore_state when a window with that name is added on a later run
+    fn serialize_state(&self) -> Option<String> {
+        None
+    }
+    fn restore_state(&mut self, _state: &str) {}
 }
 
-fn parse_value_via_definition(
+// the newest HISTORY_LIMIT commands in the file. a missing file is fine, it's created by the first
+// command run, anything unreadable gives an empty history. a file that's grown past the limit is
+// rewritten with just the kept commands.
+fn load_history(path: &Path) -> Vec<String> {
+    let text = match std::fs::read_to_string(path) {
+        Ok(x) => x,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Vec::new(),
+        Err(e) => {
+            log::warn!("couldn't read console history {path:?}, starting empty: {e}");
+            return Vec::new();
+        }
+    };
+
+    let mut history: Vec<String> = Vec::new();
+    let mut total = 0;
+    for line in text.lines().map(str::trim).filter(|x| !x.is_empty()) {
+        total += 1;
+        if history.last().map(|x| x.as_str()) != Some(line) {
+            history.push(line.to_owned());
+        }
+    }
+    if history.len() > HISTORY_LIMIT {
+        history.drain(..history.len() - HISTORY_LIMIT);
+    }
+
+    if total > history.len() {
+        let mut text = history.join("\n");
+        text.push('\n');
+        if let Err(e) = std::fs::write(path, text) {
+            log::warn!("couldn't trim console history {path:?}: {e}");
+        }
+    }
+
+    history
+}
+
+// alias to command name. aliases that clash with a command name or an earlier alias are dropped.
+fn build_alias_map(console_commands: &BTreeMap<String, ConsoleCommand>) -> BTreeMap<String, String> {
+    let mut aliases = BTreeMap::new();
+    for (name, cmd) in console_commands {
+        for alias in &cmd.aliases {
+            if console_commands.contains_key(alias) {
+                log::warn!("alias '{alias}' of console command '{name}' is already a command name, ignoring it.");
+            } else if let Some(other) = aliases.get(alias) {
+                log::warn!("alias '{alias}' of console command '{name}' is already an alias of '{other}', ignoring it.");
+            } else {
+                aliases.insert(alias.clone(), name.clone());
+            }
+        }
+    }
+    aliases
+}
+
+fn command_starts_with(name: &str, prefix: &str, case_insensitive: bool) -> bool {
+    if case_insensitive {
+        name.to_lowercase().starts_with(&prefix.to_lowercase())
+    } else {
+        name.starts_with(prefix)
+    }
+}
+
+// how well a command name matches the typed text for autocomplete, None when it doesn't match.
+// fuzzy matches take the typed letters in order anywhere in the name, scoring contiguous runs and
+// early matches higher. prefix matches always outrank other fuzzy matches.
+fn autocomplete_score(name: &str, typed: &str, case_insensitive: bool, fuzzy: bool) -> Option<i64> {
+    if command_starts_with(name, typed, case_insensitive) {
+        return Some(i64::MAX);
+    }
+    if !fuzzy {
+        return None;
+    }
+
+    let fold = |c: char| if case_insensitive { c.to_ascii_lowercase() } else { c };
+    let mut typed = typed.chars().map(fold).peekable();
+    let mut score = 0;
+    let mut run = 0;
+    let mut first_match = None;
+    for (i, c) in name.chars().map(fold).enumerate() {
+        match typed.peek() {
+            Some(t) if *t == c => {
+                typed.next();
+                run += 1;
+                score += run;
+                first_match.get_or_insert(i as i64);
+            }
+            Some(_) => run = 0,
+            None => break,
+        }
+    }
+
+    if typed.peek().is_some() {
+        return None;
+    }
+    Some(score * 4 - first_match.unwrap_or(0))
+}
+
+// splits a console line into its commands on ';' and drops anything after '//'. both are ignored
+// inside quotes or after a '\' escape, so they can still be passed in values. empty commands, e.g.
+// from a trailing ';', are left out.
+fn split_command_line(line: &str) -> Vec<String> {
+    let mut commands = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                current.push(c);
+                if let Some(escaped) = chars.next() {
+                    current.push(escaped);
+                }
+                continue;
+            }
+            '"' => in_quotes = !in_quotes,
+            ';' if !in_quotes => {
+                commands.push(std::mem::take(&mut current));
+                continue;
+            }
+            '/' if !in_quotes && chars.peek() == Some(&'/') => break,
+            _ => {}
+        }
+        current.push(c);
+    }
+    commands.push(current);
+
+    commands
+        .into_iter()
+        .map(|x| x.trim().to_owned())
+        .filter(|x| !x.is_empty())
+        .collect()
+}
+
+// overflow gets its own error so a number that's too big isn't reported as not being a number
+fn parse_integer<T: std::str::FromStr<Err = std::num::ParseIntError>>(
+    value: &String,
+    def: &CallbackArgumentDefinition,
+    type_name: &str,
+) -> Result<T, String> {
+    value.parse::<T>().map_err(|e| match e.kind() {
+        std::num::IntErrorKind::PosOverflow | std::num::IntErrorKind::NegOverflow => {
+            format!("argument '{}': value out of range for {type_name}, got {value}", def.name)
+        }
+        _ => format!("couldn't parse argument '{}' as a valid {type_name}: {e}", def.name),
+    })
+}
+
+// negative input gets its own error, the parse error for it ("invalid digit") is confusing
+fn parse_unsigned<T: std::str::FromStr<Err = std::num::ParseIntError>>(
     value: &String,
     def: &CallbackArgumentDefinition,
+    type_name: &str,
+) -> Result<T, String> {
+    if value.starts_with('-') {
+        return Err(format!("argument '{}' is a {type_name} and can't be negative, got {value}", def.name));
+    }
+    parse_integer(value, def, type_name)
+}
+
+fn parse_value_via_definition(
+    value: &CommandValue,
+    def: &CallbackArgumentDefinition,
 ) -> Result<CallbackArgumentValue, String> {
-    match def.cba_type {
-        CallbackArgumentType::Int32 => match value.parse::<i32>() {
-            Ok(x) => Ok(CallbackArgumentValue::Int32(x)),
-            Err(e) => {
-                return Err(format!("couldn't parse argument '{}' as a valid i32: {e}", def.name));
+    match &def.cba_type {
+        CallbackArgumentType::List(element_type) => {
+            // a lone "" is an empty list
+            if let [x] = value.elements.as_slice() {
+                if x.is_empty() {
+                    return Ok(CallbackArgumentValue::List(Vec::new()));
+                }
             }
-        },
-        CallbackArgumentType::Int64 => match value.parse::<i64>() {
-            Ok(x) => Ok(CallbackArgumentValue::Int64(x)),
-            Err(e) => {
-                return Err(format!("couldn't parse argument '{}' as a valid i64: {e}", def.name));
+
+            // any bad element fails the whole list
+            let mut list = Vec::new();
+            for (i, element) in value.elements.iter().enumerate() {
+                match parse_single_value(element, def, element_type) {
+                    Ok(x) => list.push(x),
+                    Err(e) => return Err(format!("{e} (list element {} '{element}')", i + 1)),
+                }
             }
-        },
+            Ok(CallbackArgumentValue::List(list))
+        }
+        cba_type => parse_single_value(&value.text, def, cba_type),
+    }
+}
+
+fn parse_single_value(
+    value: &String,
+    def: &CallbackArgumentDefinition,
+    cba_type: &CallbackArgumentType,
+) -> Result<CallbackArgumentValue, String> {
+    match cba_type {
+        CallbackArgumentType::Int32 => Ok(CallbackArgumentValue::Int32(parse_integer(value, def, "i32")?)),
+        CallbackArgumentType::Int64 => Ok(CallbackArgumentValue::Int64(parse_integer(value, def, "i64")?)),
+        CallbackArgumentType::UInt32 => Ok(CallbackArgumentValue::UInt32(parse_unsigned(value, def, "u32")?)),
+        CallbackArgumentType::UInt64 => Ok(CallbackArgumentValue::UInt64(parse_unsigned(value, def, "u64")?)),
+        CallbackArgumentType::USize => Ok(CallbackArgumentValue::USize(parse_unsigned(value, def, "usize")?)),
         CallbackArgumentType::Float32 => match value.parse::<f32>() {
             Ok(x) => Ok(CallbackArgumentValue::Float32(x)),
             Err(e) => {
@@ -1000,6 +2111,7 @@ fn parse_value_via_definition(
             }
         },
         CallbackArgumentType::Flag => Ok(CallbackArgumentValue::Flag(true)),
+        CallbackArgumentType::List(_) => Err(format!("argument '{}' can't be a list of lists", def.name)),
     }
 }
 
@@ -1041,7 +2153,7 @@ fn expand_parse_tree_node(node: &ParseTreeNode) -> String {
     val
 }
 
-fn expand_command_parameters(tree: &ParseTree) -> Vec<(String, String)> {
+fn expand_command_parameters(tree: &ParseTree) -> Vec<(String, CommandValue)> {
     let mut params = Vec::new();
 
     let mut nodes = tree.rhs_iter();
@@ -1069,7 +2181,7 @@ fn expand_command_parameters(tree: &ParseTree) -> Vec<(String, String)> {
     params
 }
 
-fn expand_command_param(command_param: &ParseTree) -> (String, String) {
+fn expand_command_param(command_param: &ParseTree) -> (String, CommandValue) {
     let param_type_node = command_param.rhs_iter().next().unwrap();
     if let ParseTreeNode::Nonterminal(nt) = param_type_node {
         let nt_name = nt.lhs.to_string();
@@ -1090,26 +2202,29 @@ fn expand_command_param(command_param: &ParseTree) -> (String, String) {
     }
 }
 
-fn expand_command_param_indexed_value(parse_tree: &ParseTree) -> (String, String) {
+fn expand_command_param_indexed_value(parse_tree: &ParseTree) -> (String, CommandValue) {
     let node = parse_tree.rhs_iter().next().unwrap();
     let indexed_tree = if let ParseTreeNode::Nonterminal(nt) = node {
         nt
     } else {
         panic!("unexpected console command parse");
     };
-    ("".to_owned(), expand_command_param_value(indexed_tree))
+    ("".to_owned(), expand_command_param_value_list(indexed_tree))
 }
 
-fn expand_command_param_flag(parse_tree: &ParseTree) -> (String, String) {
+fn expand_command_param_flag(parse_tree: &ParseTree) -> (String, CommandValue) {
     let mut node = parse_tree.rhs_iter();
     node.next().unwrap(); // --
     (
         expand_parse_tree_node(node.next().unwrap()),
-        "true".to_owned(),
+        CommandValue {
+            text: "true".to_owned(),
+            elements: vec!["true".to_owned()],
+        },
     )
 }
 
-fn expand_command_param_name_value_pair(parse_tree: &ParseTree) -> (String, String) {
+fn expand_command_param_name_value_pair(parse_tree: &ParseTree) -> (String, CommandValue) {
     let mut param_nodes = parse_tree.rhs_iter();
     let name = expand_parse_tree_node(param_nodes.next().unwrap());
 
@@ -1119,7 +2234,7 @@ fn expand_command_param_name_value_pair(parse_tree: &ParseTree) -> (String, Stri
 
     let value_node = param_nodes.next().unwrap();
     let value = if let ParseTreeNode::Nonterminal(nt) = value_node {
-        expand_command_param_value(nt)
+        expand_command_param_value_list(nt)
     } else {
         panic!("unexpected console command parse");
     };
@@ -1127,6 +2242,46 @@ fn expand_command_param_name_value_pair(parse_tree: &ParseTree) -> (String, Stri
     (name, value)
 }
 
+// a single value keeps its text as is, a list given to a non-list argument gets the text as typed
+fn expand_command_param_value_list(parse_tree: &ParseTree) -> CommandValue {
+    let mut elements = Vec::new();
+    expand_command_param_list_elements(parse_tree, &mut elements);
+
+    let text = if elements.len() == 1 {
+        elements[0].clone()
+    } else {
+        parse_tree.rhs_iter().map(expand_parse_tree_node).collect()
+    };
+    CommandValue { text, elements }
+}
+
+fn expand_command_param_list_elements(parse_tree: &ParseTree, elements: &mut Vec<String>) {
+    // <value> | <value_list> <ws_star> "," <ws_star> <value>
+    let mut nodes = parse_tree.rhs_iter();
+    let first = if let ParseTreeNode::Nonterminal(nt) = nodes.next().unwrap() {
+        nt
+    } else {
+        panic!("unexpected console command parse");
+    };
+
+    if first.lhs.to_string() == "<value>" {
+        elements.push(expand_command_param_value(first));
+        return;
+    }
+
+    expand_command_param_list_elements(first, elements);
+
+    nodes.next().unwrap(); // <ws_star>
+    nodes.next().unwrap(); // ","
+    nodes.next().unwrap(); // <ws_star>
+
+    if let ParseTreeNode::Nonterminal(nt) = nodes.next().unwrap() {
+        elements.push(expand_command_param_value(nt));
+    } else {
+        panic!("unexpected console command parse");
+    }
+}
+
 fn expand_command_param_value(parse_tree: &ParseTree) -> String {
     let mut value_nodes = parse_tree.rhs_iter();
     let first = value_nodes.next().unwrap();
@@ -1171,21 +2326,33 @@ pub struct DebugLogRecord {
     module: String,
     target: String,
     local_time: time::OffsetDateTime,
+    fields: BTreeMap<String, String>,
 }
 
+// records are queued by log and only moved into the shared list by DebugLogQueue::flush, once a
+// frame, so logging threads contend on a short push instead of the ui's read lock
 pub struct DebugConsoleLogger {
     records: Arc<RwLock<Vec<DebugLogRecord>>>,
+    pending: Arc<Mutex<Vec<DebugLogRecord>>>,
 }
 impl DebugConsoleLogger {
     pub fn new() -> Self {
         Self {
             records: Arc::new(RwLock::new(Vec::new())),
+            pending: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
     pub fn get_shared(&self) -> Arc<RwLock<Vec<DebugLogRecord>>> {
         self.records.clone()
     }
+
+    pub fn get_queue(&self) -> DebugLogQueue {
+        DebugLogQueue {
+            records: self.records.clone(),
+            pending: self.pending.clone(),
+        }
+    }
 }
 impl log::Log for DebugConsoleLogger {
     fn enabled(&self, _metadata: &log::Metadata) -> bool {
@@ -1195,12 +2362,22 @@ impl log::Log for DebugConsoleLogger {
     fn flush(&self) {}
 
     fn log(&self, record: &log::Record) {
-        let mut records = self.records.write().unwrap();
-        let idx = records.len();
+        // the collector never fails, and logging from in here would deadlock on pending
+        let mut fields = BTreeMap::new();
+        let _ = record
+            .key_values()
+            .visit(&mut LogFieldCollector(&mut fields));
+
         let text = record.args().to_string();
-        let debug_text: String = text.clone().replace("\r\n", "\n").replace("\n", "\\n");
-        records.push(DebugLogRecord {
-            idx,
+        // the table row only shows the start of the text, laying out a huge message every frame
+        // is expensive. the detail window still gets the full text.
+        let mut debug_text = match text.char_indices().nth(LOG_ROW_TEXT_LIMIT) {
+            Some((end, _)) => text[..end].to_owned() + "…",
+            None => text.clone(),
+        };
+        debug_text = debug_text.replace("\r\n", "\n").replace("\n", "\\n");
+        let record = DebugLogRecord {
+            idx: 0, // assigned on flush
             debug_text,
             text,
             level: record.level(),
@@ -1214,12 +2391,198 @@ impl log::Log for DebugConsoleLogger {
                 .and_then(|p| Some(p.to_string()))
                 .unwrap_or_default(),
             target: record.target().to_string(),
-            local_time: OffsetDateTime::now_local()
-                .map_err(|e| {
-                    log::error!("couldn't get local time: {e}");
-                })
-                .unwrap_or(OffsetDateTime::UNIX_EPOCH),
-        });
+            local_time: OffsetDateTime::now_local().unwrap_or(OffsetDateTime::UNIX_EPOCH),
+            fields,
+        };
+
+        self.pending.lock().unwrap().push(record);
+    }
+}
+
+pub struct DebugLogQueue {
+    records: Arc<RwLock<Vec<DebugLogRecord>>>,
+    pending: Arc<Mutex<Vec<DebugLogRecord>>>,
+}
+impl DebugLogQueue {
+    // moves queued records into the shared list in the order they were logged
+    pub fn flush(&self) {
+        let pending = std::mem::take(&mut *self.pending.lock().unwrap());
+        if pending.is_empty() {
+            return;
+        }
+
+        let mut records = self.records.write().unwrap();
+        for mut record in pending {
+            record.idx = records.len();
+            records.push(record);
+        }
+    }
+}
+
+// prints records to stderr, coloured the same as the console
+pub struct TerminalLogger {
+    max_level: log::Level,
+    theme: LogLevelTheme,
+}
+impl TerminalLogger {
+    pub fn new(max_level: log::Level, theme: LogLevelTheme) -> Self {
+        Self { max_level, theme }
+    }
+}
+impl log::Log for TerminalLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= self.max_level
+    }
+
+    fn flush(&self) {
+        let _ = std::io::stderr().flush();
+    }
+
+    fn log(&self, record: &log::Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let mut fields = BTreeMap::new();
+        let _ = record
+            .key_values()
+            .visit(&mut LogFieldCollector(&mut fields));
+
+        let colour = self.theme.get(record.level()).text;
+        let mut line = format!(
+            "[\x1b[38;2;{};{};{}m{}\x1b[0m] \x1b[2m{}:\x1b[0m {}",
+            colour.r(),
+            colour.g(),
+            colour.b(),
+            record.level(),
+            record.target(),
+            record.args(),
+        );
+        for (key, value) in fields {
+            line += &format!(" \x1b[2m{key}=\x1b[0m{value}");
+        }
+
+        // a closed stderr isn't worth failing over
+        let _ = writeln!(std::io::stderr().lock(), "{line}");
+    }
+}
+
+// the colours a log level is drawn with, bars are drawn behind the level's records at half the
+// ui opacity, None draws no bar
+#[derive(Clone, Copy)]
+pub struct LogLevelColours {
+    pub text: Color32,
+    pub bar: Option<Color32>,
+}
+
+// per-level colours for the console log and the terminal logger
+#[derive(Clone)]
+pub struct LogLevelTheme {
+    pub error: LogLevelColours,
+    pub warn: LogLevelColours,
+    pub info: LogLevelColours,
+    pub debug: LogLevelColours,
+    pub trace: LogLevelColours,
+}
+impl LogLevelTheme {
+    pub fn get(&self, level: log::Level) -> LogLevelColours {
+        match level {
+            log::Level::Error => self.error,
+            log::Level::Warn => self.warn,
+            log::Level::Info => self.info,
+            log::Level::Debug => self.debug,
+            log::Level::Trace => self.trace,
+        }
+    }
+
+    pub fn high_contrast() -> Self {
+        Self {
+            error: LogLevelColours {
+                text: Color32::from_rgb(255, 96, 96),
+                bar: Some(Color32::from_rgb(110, 0, 0)),
+            },
+            warn: LogLevelColours {
+                text: Color32::from_rgb(255, 230, 0),
+                bar: Some(Color32::from_rgb(90, 80, 0)),
+            },
+            info: LogLevelColours {
+                text: Color32::from_rgb(255, 255, 255),
+                bar: None,
+            },
+            debug: LogLevelColours {
+                text: Color32::from_rgb(0, 230, 255),
+                bar: None,
+            },
+            trace: LogLevelColours {
+                text: Color32::from_rgb(190, 190, 190),
+                bar: None,
+            },
+        }
+    }
+
+    // okabe-ito colours, errors and warnings also differ in brightness so they don't rely on hue
+    pub fn colourblind() -> Self {
+        Self {
+            error: LogLevelColours {
+                text: Color32::from_rgb(213, 94, 0),
+                bar: Some(Color32::from_rgb(72, 32, 0)),
+            },
+            warn: LogLevelColours {
+                text: Color32::from_rgb(240, 228, 66),
+                bar: Some(Color32::from_rgb(64, 60, 12)),
+            },
+            info: LogLevelColours {
+                text: Color32::from_rgb(86, 180, 233),
+                bar: None,
+            },
+            debug: LogLevelColours {
+                text: Color32::from_rgb(204, 121, 167),
+                bar: None,
+            },
+            trace: LogLevelColours {
+                text: Color32::from_rgb(165, 175, 185),
+                bar: None,
+            },
+        }
+    }
+}
+impl Default for LogLevelTheme {
+    fn default() -> Self {
+        Self {
+            error: LogLevelColours {
+                text: Color32::LIGHT_RED,
+                bar: Some(Color32::from_rgb(64, 8, 8)),
+            },
+            warn: LogLevelColours {
+                text: Color32::LIGHT_YELLOW,
+                bar: Some(Color32::from_rgb(64, 64, 0)),
+            },
+            info: LogLevelColours {
+                text: Color32::LIGHT_GREEN,
+                bar: None,
+            },
+            debug: LogLevelColours {
+                text: Color32::GOLD,
+                bar: None,
+            },
+            trace: LogLevelColours {
+                text: Color32::LIGHT_BLUE,
+                bar: None,
+            },
+        }
+    }
+}
+
+// gathers a record's structured key/value pairs, e.g. log::info!(entity_id = 4; "spawned")
+struct LogFieldCollector<'a>(&'a mut BTreeMap<String, String>);
+impl<'a, 'kvs> log::kv::VisitSource<'kvs> for LogFieldCollector<'a> {
+    fn visit_pair(
+        &mut self,
+        key: log::kv::Key<'kvs>,
+        value: log::kv::Value<'kvs>,
+    ) -> Result<(), log::kv::Error> {
+        self.0.insert(key.to_string(), value.to_string());
+        Ok(())
     }
 }
 
@@ -1229,3 +2592,89 @@ struct RecordWindow {
     wrap_text: bool,
     fake_text: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn def(cba_type: CallbackArgumentType) -> CallbackArgumentDefinition {
+        CallbackArgumentDefinition {
+            name: "x".to_owned(),
+            cba_type,
+            optional: false,
+            description: None,
+        }
+    }
+
+    fn parse(value: &str, cba_type: CallbackArgumentType) -> Result<CallbackArgumentValue, String> {
+        let def = def(cba_type);
+        parse_single_value(&value.to_owned(), &def, &def.cba_type)
+    }
+
+    // keeps the examples from drifting from what the console actually accepts
+    #[test]
+    fn syntax_examples_parse() {
+        let grammar: bnf::Grammar = COMMAND_GRAMMAR.parse().unwrap();
+        for example in SYNTAX_EXAMPLES {
+            for cmd in split_command_line(example) {
+                assert!(grammar.parse_input(&cmd).count() == 1, "syntax example '{cmd}' doesn't parse");
+            }
+        }
+    }
+
+    #[test]
+    fn builtin_aliases_resolve() {
+        let console = DebugConsole::new(BTreeMap::new(), BTreeMap::new(), LogLevelTheme::default(), None);
+        assert_eq!(console.resolve_command_name("cls").as_deref(), Some("clear"));
+        assert_eq!(console.resolve_command_name("k9_case_insensitive").as_deref(), Some("k9_console_case_insensitive"));
+    }
+
+    #[test]
+    fn alias_stores_the_resolved_command() {
+        let mut console = DebugConsole::new(BTreeMap::new(), BTreeMap::new(), LogLevelTheme::default(), None);
+        *console.case_insensitive.lock().unwrap() = true;
+        assert_eq!(console.set_user_alias("h", "HELP"), Ok("help".to_owned()));
+        assert_eq!(console.set_user_alias("hh", "h"), Ok("help".to_owned()));
+        assert_eq!(console.resolve_command_name("h").as_deref(), Some("help"));
+        assert_eq!(console.resolve_command_name("HH").as_deref(), Some("help"));
+        assert!(console.set_user_alias("help", "h").is_err());
+    }
+
+    #[test]
+    fn parse_i32_boundaries() {
+        assert!(matches!(parse("2147483647", CallbackArgumentType::Int32), Ok(CallbackArgumentValue::Int32(i32::MAX))));
+        assert!(matches!(parse("-2147483648", CallbackArgumentType::Int32), Ok(CallbackArgumentValue::Int32(i32::MIN))));
+        assert_eq!(
+            parse("2147483648", CallbackArgumentType::Int32).unwrap_err(),
+            "argument 'x': value out of range for i32, got 2147483648"
+        );
+        assert_eq!(
+            parse("-2147483649", CallbackArgumentType::Int32).unwrap_err(),
+            "argument 'x': value out of range for i32, got -2147483649"
+        );
+    }
+
+    #[test]
+    fn parse_u32_boundaries() {
+        assert!(matches!(parse("4294967295", CallbackArgumentType::UInt32), Ok(CallbackArgumentValue::UInt32(u32::MAX))));
+        assert!(matches!(parse("0", CallbackArgumentType::UInt32), Ok(CallbackArgumentValue::UInt32(0))));
+        assert_eq!(
+            parse("4294967296", CallbackArgumentType::UInt32).unwrap_err(),
+            "argument 'x': value out of range for u32, got 4294967296"
+        );
+        assert_eq!(
+            parse("-1", CallbackArgumentType::UInt32).unwrap_err(),
+            "argument 'x' is a u32 and can't be negative, got -1"
+        );
+    }
+
+    #[test]
+    fn parse_non_numeric_integers() {
+        for value in ["abc", "12abc", "1.5", ""] {
+            let err = parse(value, CallbackArgumentType::Int32).unwrap_err();
+            assert!(err.starts_with("couldn't parse argument 'x' as a valid i32"), "{value:?}: {err}");
+            let err = parse(value, CallbackArgumentType::UInt64).unwrap_err();
+            assert!(err.starts_with("couldn't parse argument 'x' as a valid u64"), "{value:?}: {err}");
+        }
+    }
+}
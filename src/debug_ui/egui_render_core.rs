@@ -5,6 +5,8 @@ use egui::{epaint::Primitive, PaintCallbackInfo};
 use glow::HasContext;
 use sdl2::clipboard::ClipboardUtil;
 
+use crate::shaders;
+
 const SCROLL_SCALE: f32 = 20.0;
 
 pub(super) struct EguiRenderCore {
@@ -20,9 +22,18 @@ pub(super) struct EguiRenderCore {
     vbo: glow::NativeBuffer,
     ebo: glow::NativeBuffer,
     sdl_cursor: Option<*mut sdl2::sys::SDL_Cursor>,
+    window_id: u32,
+    // when set the ui is painted here first and composited onto the default framebuffer after
+    offscreen: Option<OffscreenTarget>,
+}
+struct OffscreenTarget {
+    fbo: glow::NativeFramebuffer,
+    texture: glow::NativeTexture,
+    // None until the first render sizes it to the screen
+    dimensions: Option<(u32, u32)>,
 }
 impl EguiRenderCore {
-    pub fn new(glow: &glow::Context, default_ppt: f32) -> Self {
+    pub fn new(glow: &glow::Context, default_ppt: f32, window_id: u32) -> Self {
         let ctx = egui::Context::default();
         let input = egui::RawInput::default();
         let modifiers = ModifierTracker::new();
@@ -36,9 +47,7 @@ impl EguiRenderCore {
                     panic!("failed to create egui debug ui vert shader: {e}");
                 }
             };
-            const VERT_SRC: &'static str = include_str!("./k9_egui_debug_ui.vert.glsl");
-
-            glow.shader_source(vert_shader, VERT_SRC);
+            glow.shader_source(vert_shader, shaders::EGUI_DEBUG_UI_VERT);
             glow.compile_shader(vert_shader);
 
             if !glow.get_shader_compile_status(vert_shader) {
@@ -53,9 +62,7 @@ impl EguiRenderCore {
                     panic!("failed to create egui debug ui frag shader: {e}");
                 }
             };
-            const FRAG_SRC: &'static str = include_str!("./k9_egui_debug_ui.frag.glsl");
-
-            glow.shader_source(frag_shader, FRAG_SRC);
+            glow.shader_source(frag_shader, shaders::EGUI_DEBUG_UI_FRAG);
             glow.compile_shader(frag_shader);
 
             if !glow.get_shader_compile_status(frag_shader) {
@@ -82,8 +89,12 @@ impl EguiRenderCore {
                 panic!("couldn't link egui debug ui program: {err}");
             }
 
-            let u_screen_size = glow.get_uniform_location(program, "u_screen_size").unwrap();
-            let u_sampler = glow.get_uniform_location(program, "u_sampler").unwrap();
+            let u_screen_size = glow
+                .get_uniform_location(program, shaders::EGUI_DEBUG_UI_SCREEN_SIZE)
+                .unwrap();
+            let u_sampler = glow
+                .get_uniform_location(program, shaders::EGUI_DEBUG_UI_SAMPLER)
+                .unwrap();
 
             let vao = glow.create_vertex_array().unwrap();
             let vbo = glow.create_buffer().unwrap();
@@ -94,33 +105,36 @@ impl EguiRenderCore {
 
             const VERTEX_SIZE: i32 = std::mem::size_of::<EguiVertexPod>() as i32;
 
+            let (pos, _) = shaders::EGUI_DEBUG_UI_POS;
+            let (uv, _) = shaders::EGUI_DEBUG_UI_UV;
+            let (colour, _) = shaders::EGUI_DEBUG_UI_COLOUR;
             glow.vertex_attrib_pointer_f32(
-                0,
+                pos,
                 2,
                 glow::FLOAT,
                 false,
                 VERTEX_SIZE,
                 offset_of!(EguiVertexPod, pos) as i32,
             );
-            glow.enable_vertex_attrib_array(0);
+            glow.enable_vertex_attrib_array(pos);
             glow.vertex_attrib_pointer_f32(
-                1,
+                uv,
                 2,
                 glow::FLOAT,
                 false,
                 VERTEX_SIZE,
                 offset_of!(EguiVertexPod, uv) as i32,
             );
-            glow.enable_vertex_attrib_array(1);
+            glow.enable_vertex_attrib_array(uv);
             glow.vertex_attrib_pointer_f32(
-                2,
+                colour,
                 4,
                 glow::UNSIGNED_BYTE,
                 false,
                 VERTEX_SIZE,
                 offset_of!(EguiVertexPod, colour) as i32,
             );
-            glow.enable_vertex_attrib_array(2);
+            glow.enable_vertex_attrib_array(colour);
 
             glow.bind_vertex_array(None);
             glow.bind_buffer(glow::ARRAY_BUFFER, None);
@@ -138,10 +152,53 @@ impl EguiRenderCore {
                 vbo,
                 ebo,
                 sdl_cursor: None,
+                window_id,
+                offscreen: None,
             }
         }
     }
 
+    pub fn set_render_offscreen(&mut self, glow: &glow::Context, enabled: bool) {
+        match (enabled, self.offscreen.take()) {
+            (true, None) => unsafe {
+                let texture = match glow.create_texture() {
+                    Ok(x) => x,
+                    Err(e) => {
+                        log::error!("couldn't create debug ui offscreen texture: {e}");
+                        return;
+                    }
+                };
+                let fbo = match glow.create_framebuffer() {
+                    Ok(x) => x,
+                    Err(e) => {
+                        log::error!("couldn't create debug ui offscreen framebuffer: {e}");
+                        glow.delete_texture(texture);
+                        return;
+                    }
+                };
+                self.offscreen = Some(OffscreenTarget {
+                    fbo,
+                    texture,
+                    dimensions: None,
+                });
+            },
+            (false, Some(target)) => unsafe {
+                glow.delete_framebuffer(target.fbo);
+                glow.delete_texture(target.texture);
+            },
+            (_, target) => self.offscreen = target,
+        }
+    }
+
+    // premultiplied rgba, bottom row first as with any gl render target
+    pub fn offscreen_texture(&self) -> Option<glow::NativeTexture> {
+        self.offscreen.as_ref().map(|x| x.texture)
+    }
+
+    pub fn window_id(&self) -> u32 {
+        self.window_id
+    }
+
     pub fn begin_frame(
         &mut self,
         window_has_focus: bool,
@@ -190,6 +247,13 @@ impl EguiRenderCore {
         self.input.modifiers = egui_modifiers;
 
         for event in sdl_events {
+            // events tied to another window are none of egui's business
+            if let Some(id) = event.get_window_id() {
+                if id != self.window_id {
+                    continue;
+                }
+            }
+
             match event {
                 sdl2::event::Event::MouseButtonDown {
                     timestamp: _,
@@ -450,6 +514,47 @@ impl EguiRenderCore {
             }
         }
 
+        if let Some(target) = &mut self.offscreen {
+            unsafe {
+                glow.bind_framebuffer(glow::FRAMEBUFFER, Some(target.fbo));
+                if target.dimensions != Some(screen_dimensions) {
+                    target.dimensions = Some(screen_dimensions);
+                    glow.bind_texture(glow::TEXTURE_2D, Some(target.texture));
+                    glow.tex_image_2d(
+                        glow::TEXTURE_2D,
+                        0,
+                        glow::RGBA8 as i32,
+                        screen_dimensions.0 as i32,
+                        screen_dimensions.1 as i32,
+                        0,
+                        glow::RGBA,
+                        glow::UNSIGNED_BYTE,
+                        None,
+                    );
+                    glow.tex_parameter_i32(
+                        glow::TEXTURE_2D,
+                        glow::TEXTURE_MIN_FILTER,
+                        glow::NEAREST as i32,
+                    );
+                    glow.tex_parameter_i32(
+                        glow::TEXTURE_2D,
+                        glow::TEXTURE_MAG_FILTER,
+                        glow::NEAREST as i32,
+                    );
+                    glow.framebuffer_texture_2d(
+                        glow::FRAMEBUFFER,
+                        glow::COLOR_ATTACHMENT0,
+                        glow::TEXTURE_2D,
+                        Some(target.texture),
+                        0,
+                    );
+                }
+                glow.disable(glow::SCISSOR_TEST);
+                glow.clear_color(0.0, 0.0, 0.0, 0.0);
+                glow.clear(glow::COLOR_BUFFER_BIT);
+            }
+        }
+
         // todo: verify that switching checking the ctx scale for ui works here
         self.paint_primitives(
             glow,
@@ -458,6 +563,13 @@ impl EguiRenderCore {
             clipped_primitives,
         );
 
+        if let Some(texture) = self.offscreen_texture() {
+            unsafe {
+                glow.bind_framebuffer(glow::FRAMEBUFFER, None);
+            }
+            self.composite_offscreen(glow, screen_dimensions, texture);
+        }
+
         // free textures
         for id in textures_delta.free {
             if let Some(tex) = self.textures.remove(&id) {
@@ -519,7 +631,7 @@ impl EguiRenderCore {
                         };
 
                         if let Some(callback) = callback.callback.downcast_ref::<CallbackFn>() {
-                            (callback.f)(info, self);
+                            (callback.f)(info, glow);
                         } else {
                             log::warn!("Warning: Unsupported render callback. Expected CallbackFn");
                         }
@@ -667,41 +779,81 @@ impl EguiRenderCore {
         }
     }
 
-    fn paint_mesh(&mut self, glow: &glow::Context, mesh: egui::Mesh) {
-        if let Some(texture) = self.textures.get(&mesh.texture_id) {
-            unsafe {
-                let vertices: Vec<EguiVertexPod> = mesh
-                    .vertices
-                    .into_iter()
-                    .map(|e| EguiVertexPod::from(e))
-                    .collect();
-                let vertices_ref: &[u8] = bytemuck::cast_slice(vertices.as_slice());
-                glow.bind_buffer(glow::ARRAY_BUFFER, Some(self.vbo));
-                glow.buffer_data_u8_slice(glow::ARRAY_BUFFER, vertices_ref, glow::STREAM_DRAW);
-
-                glow.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(self.ebo));
-                glow.buffer_data_u8_slice(
-                    glow::ELEMENT_ARRAY_BUFFER,
-                    bytemuck::cast_slice(&mesh.indices),
-                    glow::STREAM_DRAW,
-                );
+    // the offscreen texture is already premultiplied, which is what egui's blend func expects
+    fn composite_offscreen(
+        &mut self,
+        glow: &glow::Context,
+        screen_size_px: (u32, u32),
+        texture: glow::NativeTexture,
+    ) {
+        let screen_scale = self.ctx.pixels_per_point();
+        let screen_rect = egui::Rect::from_min_size(
+            egui::Pos2::ZERO,
+            egui::vec2(
+                screen_size_px.0 as f32 / screen_scale,
+                screen_size_px.1 as f32 / screen_scale,
+            ),
+        );
+        // flipped, the texture's first row is the bottom of the screen
+        let uv = egui::Rect::from_min_max(egui::pos2(0.0, 1.0), egui::pos2(1.0, 0.0));
+        let mut mesh = egui::Mesh::default();
+        mesh.add_rect_with_uv(screen_rect, uv, egui::Color32::WHITE);
 
-                glow.bind_texture(glow::TEXTURE_2D, Some(*texture));
-            }
+        self.prepare_painting(glow, screen_size_px, screen_scale);
+        unsafe {
+            glow.disable(glow::SCISSOR_TEST);
+        }
+        self.paint_mesh_with_texture(glow, mesh, texture);
+        unsafe {
+            glow.bind_vertex_array(None);
+            glow.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, None);
+        }
+    }
 
-            unsafe {
-                glow.draw_elements(
-                    glow::TRIANGLES,
-                    mesh.indices.len() as i32,
-                    glow::UNSIGNED_INT,
-                    0,
-                );
-            }
+    fn paint_mesh(&mut self, glow: &glow::Context, mesh: egui::Mesh) {
+        if let Some(texture) = self.textures.get(&mesh.texture_id) {
+            self.paint_mesh_with_texture(glow, mesh, *texture);
         } else {
             log::error!("egui failed to find texture {:?}", mesh.texture_id);
         }
     }
 
+    fn paint_mesh_with_texture(
+        &mut self,
+        glow: &glow::Context,
+        mesh: egui::Mesh,
+        texture: glow::NativeTexture,
+    ) {
+        unsafe {
+            let vertices: Vec<EguiVertexPod> = mesh
+                .vertices
+                .into_iter()
+                .map(|e| EguiVertexPod::from(e))
+                .collect();
+            let vertices_ref: &[u8] = bytemuck::cast_slice(vertices.as_slice());
+            glow.bind_buffer(glow::ARRAY_BUFFER, Some(self.vbo));
+            glow.buffer_data_u8_slice(glow::ARRAY_BUFFER, vertices_ref, glow::STREAM_DRAW);
+
+            glow.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(self.ebo));
+            glow.buffer_data_u8_slice(
+                glow::ELEMENT_ARRAY_BUFFER,
+                bytemuck::cast_slice(&mesh.indices),
+                glow::STREAM_DRAW,
+            );
+
+            glow.bind_texture(glow::TEXTURE_2D, Some(texture));
+        }
+
+        unsafe {
+            glow.draw_elements(
+                glow::TRIANGLES,
+                mesh.indices.len() as i32,
+                glow::UNSIGNED_INT,
+                0,
+            );
+        }
+    }
+
     pub fn handle_platform_output(
         &mut self,
         output: egui::PlatformOutput,
@@ -913,11 +1065,11 @@ fn sdl_keycode_to_egui_key(keycode: &sdl2::keyboard::Keycode) -> Option<egui::Ke
 }
 
 pub(super) struct CallbackFn {
-    f: Box<dyn Fn(PaintCallbackInfo, &EguiRenderCore) + Sync + Send>,
+    f: Box<dyn Fn(PaintCallbackInfo, &glow::Context) + Sync + Send>,
 }
 
 impl CallbackFn {
-    pub fn new<F: Fn(PaintCallbackInfo, &EguiRenderCore) + Sync + Send + 'static>(
+    pub fn new<F: Fn(PaintCallbackInfo, &glow::Context) + Sync + Send + 'static>(
         callback: F,
     ) -> Self {
         let f = Box::new(callback);
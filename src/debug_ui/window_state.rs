@@ -0,0 +1,84 @@
+use std::{collections::BTreeMap, path::Path};
+
+// debug window state file, one entry per window:
+// "<key byte len> <state byte len>\n<key><state>\n"
+// lengths rather than separators so states can hold anything, including newlines.
+
+pub(super) fn read(path: &Path) -> Result<BTreeMap<String, String>, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let mut rest = text.as_str();
+    let mut states = BTreeMap::new();
+
+    while !rest.is_empty() {
+        let (header, body) = rest.split_once('\n').ok_or("missing entry header")?;
+        let (key_len, state_len) = header
+            .split_once(' ')
+            .and_then(|(k, s)| Some((k.parse::<usize>().ok()?, s.parse::<usize>().ok()?)))
+            .ok_or(format!("bad entry header '{header}'"))?;
+
+        let key = body.get(..key_len).ok_or("entry key out of bounds")?;
+        let state_end = key_len
+            .checked_add(state_len)
+            .ok_or(format!("state for '{key}' out of bounds"))?;
+        let state = body
+            .get(key_len..state_end)
+            .ok_or(format!("state for '{key}' out of bounds"))?;
+        rest = body
+            .get(state_end..)
+            .and_then(|x| x.strip_prefix('\n'))
+            .ok_or(format!("missing newline after state for '{key}'"))?;
+
+        states.insert(key.to_owned(), state.to_owned());
+    }
+
+    Ok(states)
+}
+
+pub(super) fn write(path: &Path, states: &BTreeMap<String, String>) -> Result<(), String> {
+    let mut text = String::new();
+    for (key, state) in states {
+        text += &format!("{} {}\n{key}{state}\n", key.len(), state.len());
+    }
+    std::fs::write(path, text).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path() -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("k9_window_state_{}", uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn round_trip_keeps_newlines() {
+        let path = temp_path();
+        let states = BTreeMap::from([
+            ("window/console".to_owned(), "open\n\nsize 3\n".to_owned()),
+            ("k9/debug_ui".to_owned(), String::new()),
+        ]);
+        write(&path, &states).unwrap();
+        let read_back = read(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read_back.unwrap(), states);
+    }
+
+    #[test]
+    fn bad_files_are_errors() {
+        let bad = [
+            "1 2",
+            "x 2\nabc\n",
+            "1\nabc\n",
+            "1 5\nabc\n",
+            "1 2\nabc",
+            "1 18446744073709551615\nabc\n",
+        ];
+        for text in bad {
+            let path = temp_path();
+            std::fs::write(&path, text).unwrap();
+            let result = read(&path);
+            std::fs::remove_file(&path).unwrap();
+            assert!(result.is_err(), "{text:?} read as {result:?}");
+        }
+    }
+}
@@ -35,6 +35,11 @@ impl Entity {
         self.components.remove(uuid)
     }
 
+    // Component::NAME of each component, in no particular order
+    pub fn component_names(&self) -> Vec<&'static str> {
+        self.components.values().map(|c| c.name).collect()
+    }
+
     pub fn has_component<T: Component + 'static>(&self) -> bool {
         self.components.contains_key(&T::UUID)
     }
@@ -64,6 +69,7 @@ impl Entity {
 
 pub struct ComponentBase {
     inner: Box<dyn Any>,
+    name: &'static str,
 }
 impl ComponentBase {}
 
@@ -74,8 +80,72 @@ pub trait Component: Sized + 'static {
     fn create_component_base(self) -> ComponentBase {
         ComponentBase {
             inner: Box::new(self),
+            name: Self::NAME,
+        }
+    }
+}
+
+// marker for entities that survive the reset_scene console command
+pub struct Persistent;
+impl Component for Persistent {
+    const NAME: &'static str = "Persistent";
+    const UUID: Uuid = uuid::uuid!("5f0b7a36-92c4-4f1e-8a53-2d6e4c1b9e07");
+}
+
+// hierarchy links, maintained by EntityTable::set_parent and detach. ids are entity table ids.
+pub struct ParentComponent {
+    parent: Uuid,
+}
+impl ParentComponent {
+    pub fn parent(&self) -> Uuid {
+        self.parent
+    }
+}
+impl Component for ParentComponent {
+    const NAME: &'static str = "Parent";
+    const UUID: Uuid = uuid::uuid!("b3e1c2d4-6a7f-4c85-9e10-4f2a8d7c5b63");
+}
+
+pub struct ChildrenComponent {
+    children: Vec<Uuid>,
+}
+impl ChildrenComponent {
+    pub fn children(&self) -> &[Uuid] {
+        &self.children
+    }
+}
+impl Component for ChildrenComponent {
+    const NAME: &'static str = "Children";
+    const UUID: Uuid = uuid::uuid!("0d9f4e8a-2b61-47c3-a5d8-7e3c1f6b9a24");
+}
+
+// an entity's transform relative to its parent, or to the world when it has none. the graphics
+// system fills in the world transform each frame before rendering, an entity in the hierarchy
+// without one of these passes its parent's world transform on to its children.
+pub struct TransformComponent {
+    pub local: glam::Mat4,
+    world: glam::Mat4,
+}
+impl TransformComponent {
+    pub fn new(local: glam::Mat4) -> Self {
+        Self {
+            local,
+            world: local,
         }
     }
+
+    // as of the last graphics update
+    pub fn world(&self) -> glam::Mat4 {
+        self.world
+    }
+
+    pub(crate) fn set_world(&mut self, world: glam::Mat4) {
+        self.world = world;
+    }
+}
+impl Component for TransformComponent {
+    const NAME: &'static str = "Transform";
+    const UUID: Uuid = uuid::uuid!("7c2a9e41-d8b3-4f60-9a1e-5b4d3c8f2e76");
 }
 
 pub struct EntityTable {
@@ -105,7 +175,17 @@ impl EntityTable {
         self.entities.insert(id, UnsafeCell::new(entity));
     }
 
+    // the entity stays in component_entity_map until clear_deleted runs at the end of the frame,
+    // that's how get_by_component_delete finds it. get_by_component skips it straight away.
+    // children of a deleted entity are detached rather than deleted with it.
     pub fn delete_entity(&mut self, uuid: &Uuid) -> bool {
+        if self.entities.contains_key(uuid) {
+            self.detach(*uuid);
+            for child in self.children_of(*uuid).to_vec() {
+                self.detach(child);
+            }
+        }
+
         if let Some(ent) = self.entities.remove(uuid) {
             self.delete_entities.insert(*uuid, ent);
             true
@@ -114,6 +194,165 @@ impl EntityTable {
         }
     }
 
+    // deletes every entity without a T component, returns how many were deleted
+    pub fn delete_all_without<T: Component + 'static>(&mut self) -> usize {
+        let ids: Vec<Uuid> = self
+            .entities
+            .iter()
+            .filter(|(_, ent)| unsafe { !(*ent.get()).has_component::<T>() })
+            .map(|(id, _)| *id)
+            .collect();
+        for id in &ids {
+            self.delete_entity(id);
+        }
+        ids.len()
+    }
+
+    // makes child a child of parent, replacing any parent it had. fails when either entity
+    // doesn't exist or parent is child or one of its descendants.
+    pub fn set_parent(&mut self, child: Uuid, parent: Uuid) -> Result<(), String> {
+        if !self.entities.contains_key(&child) {
+            return Err(format!("no entity with id {child}"));
+        }
+        if !self.entities.contains_key(&parent) {
+            return Err(format!("no entity with id {parent}"));
+        }
+
+        // walking up from the new parent reaches child if the link would close a cycle
+        let mut cursor = Some(parent);
+        while let Some(id) = cursor {
+            if id == child {
+                return Err(format!(
+                    "making {parent} the parent of {child} would create a cycle"
+                ));
+            }
+            cursor = self.parent_of(id);
+        }
+
+        self.detach(child);
+        self.insert_component(child, ParentComponent { parent });
+        let children = self
+            .get_by_uuid_mut(&parent)
+            .and_then(|x| x.get_component_mut::<ChildrenComponent>());
+        match children {
+            Some(x) => x.children.push(child),
+            None => self.insert_component(
+                parent,
+                ChildrenComponent {
+                    children: vec![child],
+                },
+            ),
+        }
+        Ok(())
+    }
+
+    // removes child from its parent, false if it didn't have one
+    pub fn detach(&mut self, child: Uuid) -> bool {
+        let parent = match self.parent_of(child) {
+            Some(x) => x,
+            None => return false,
+        };
+        self.remove_component::<ParentComponent>(child);
+
+        let now_empty = match self
+            .get_by_uuid_mut(&parent)
+            .and_then(|x| x.get_component_mut::<ChildrenComponent>())
+        {
+            Some(x) => {
+                x.children.retain(|id| *id != child);
+                x.children.is_empty()
+            }
+            None => false,
+        };
+        if now_empty {
+            self.remove_component::<ChildrenComponent>(parent);
+        }
+        true
+    }
+
+    pub fn parent_of(&self, child: Uuid) -> Option<Uuid> {
+        self.get_by_uuid(&child)?
+            .get_component::<ParentComponent>()
+            .map(|x| x.parent)
+    }
+
+    // empty for entities without children, in the order they were parented
+    pub fn children_of(&self, parent: Uuid) -> &[Uuid] {
+        self.get_by_uuid(&parent)
+            .and_then(|x| x.get_component::<ChildrenComponent>())
+            .map(|x| x.children.as_slice())
+            .unwrap_or(&[])
+    }
+
+    // depth first over every descendant of root, parents before their children. visit gets the
+    // id and its depth below root, starting at 1 for root's children. this is the order to
+    // propagate anything that's relative to the parent, like transforms.
+    pub fn walk_hierarchy(&self, root: Uuid, mut visit: impl FnMut(Uuid, usize)) {
+        let mut stack: Vec<(Uuid, usize)> = self
+            .children_of(root)
+            .iter()
+            .rev()
+            .map(|id| (*id, 1))
+            .collect();
+        while let Some((id, depth)) = stack.pop() {
+            visit(id, depth);
+            stack.extend(self.children_of(id).iter().rev().map(|x| (*x, depth + 1)));
+        }
+    }
+
+    // adds a component to an entity already in the table, keeping component_entity_map in sync
+    fn insert_component<T: Component>(&mut self, id: Uuid, component: T) {
+        if let Some(ent) = self.entities.get_mut(&id) {
+            ent.get_mut()
+                .components
+                .insert(T::UUID, component.create_component_base());
+            self.component_entity_map
+                .entry(T::UUID)
+                .or_default()
+                .insert(id);
+        }
+    }
+    fn remove_component<T: Component>(&mut self, id: Uuid) {
+        if let Some(ent) = self.entities.get_mut(&id) {
+            ent.get_mut().components.remove(&T::UUID);
+            if let Some(ids) = self.component_entity_map.get_mut(&T::UUID) {
+                ids.remove(&id);
+                if ids.is_empty() {
+                    self.component_entity_map.remove(&T::UUID);
+                }
+            }
+        }
+    }
+
+    // drops entities deleted since the last call, systems must have seen them by now since
+    // they're gone from get_by_component_delete afterwards
+    pub(crate) fn clear_deleted(&mut self) {
+        for (id, ent) in std::mem::take(&mut self.delete_entities) {
+            for comp_id in ent.into_inner().components.keys() {
+                if let Some(ids) = self.component_entity_map.get_mut(comp_id) {
+                    ids.remove(&id);
+                    if ids.is_empty() {
+                        self.component_entity_map.remove(comp_id);
+                    }
+                }
+            }
+        }
+    }
+
+    // one line per entity, "<id>: <component names>", ids are the ones delete_entity takes
+    pub fn describe(&self) -> String {
+        let lines: Vec<String> = self
+            .entities
+            .iter()
+            .map(|(id, ent)| {
+                let mut names = unsafe { (*ent.get()).component_names() };
+                names.sort();
+                format!("{id}: {}", names.join(", "))
+            })
+            .collect();
+        lines.join("\n")
+    }
+
     pub fn all(&self) -> Vec<&Entity> {
         self.entities
             .values()
@@ -158,6 +397,65 @@ impl EntityTable {
         }
         Some(m)
     }
+    // entities with every one of the components, None if any of them isn't on any entity
+    pub fn get_by_components_2<T: Component + 'static, U: Component + 'static>(
+        &self,
+    ) -> Option<BTreeMap<Uuid, &Entity>> {
+        Some(self.entities_by_ids(self.ids_with_all(&[T::UUID, U::UUID])?))
+    }
+    pub fn get_by_components_2_mut<T: Component + 'static, U: Component + 'static>(
+        &mut self,
+    ) -> Option<BTreeMap<Uuid, &mut Entity>> {
+        Some(self.entities_by_ids_mut(self.ids_with_all(&[T::UUID, U::UUID])?))
+    }
+    pub fn get_by_components_3<
+        T: Component + 'static,
+        U: Component + 'static,
+        V: Component + 'static,
+    >(
+        &self,
+    ) -> Option<BTreeMap<Uuid, &Entity>> {
+        Some(self.entities_by_ids(self.ids_with_all(&[T::UUID, U::UUID, V::UUID])?))
+    }
+    pub fn get_by_components_3_mut<
+        T: Component + 'static,
+        U: Component + 'static,
+        V: Component + 'static,
+    >(
+        &mut self,
+    ) -> Option<BTreeMap<Uuid, &mut Entity>> {
+        Some(self.entities_by_ids_mut(self.ids_with_all(&[T::UUID, U::UUID, V::UUID])?))
+    }
+
+    // intersects the component sets starting from the smallest, so the cost follows the rarest
+    // component rather than the most common one
+    fn ids_with_all(&self, comp_ids: &[Uuid]) -> Option<Vec<Uuid>> {
+        let mut sets = Vec::with_capacity(comp_ids.len());
+        for comp_id in comp_ids {
+            sets.push(self.component_entity_map.get(comp_id)?);
+        }
+        sets.sort_by_key(|x| x.len());
+
+        let (smallest, rest) = sets.split_first()?;
+        Some(
+            smallest
+                .iter()
+                .filter(|id| rest.iter().all(|set| set.contains(id)))
+                .copied()
+                .collect(),
+        )
+    }
+    fn entities_by_ids(&self, ids: Vec<Uuid>) -> BTreeMap<Uuid, &Entity> {
+        ids.into_iter()
+            .filter_map(|id| Some((id, unsafe { &*self.entities.get(&id)?.get() })))
+            .collect()
+    }
+    fn entities_by_ids_mut(&mut self, ids: Vec<Uuid>) -> BTreeMap<Uuid, &mut Entity> {
+        ids.into_iter()
+            .filter_map(|id| Some((id, unsafe { &mut *self.entities.get(&id)?.get() })))
+            .collect()
+    }
+
     pub fn get_by_component_delete<T: Component + 'static>(
         &self,
     ) -> Option<BTreeMap<Uuid, &Entity>> {
@@ -191,3 +489,52 @@ impl EntityTable {
         self.entities.get_mut(uuid).map(|e| e.get_mut())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct A;
+    impl Component for A {
+        const NAME: &'static str = "A";
+        const UUID: Uuid = uuid::uuid!("3d4c7f0a-1b2e-4c6d-9a8f-5e7b0c1d2a34");
+    }
+    struct B;
+    impl Component for B {
+        const NAME: &'static str = "B";
+        const UUID: Uuid = uuid::uuid!("8a1f2e3d-4c5b-4a69-8b7c-0d1e2f3a4b5c");
+    }
+
+    #[test]
+    fn clear_deleted_cleans_the_component_map() {
+        let mut ents = EntityTable::new();
+        let mut both = Entity::new();
+        both.add_component(A);
+        both.add_component(B);
+        ents.add_new_entity(both);
+        let mut only_a = Entity::new();
+        only_a.add_component(A);
+        ents.add_new_entity(only_a);
+
+        let both_id = *ents.component_entity_map[&B::UUID].first().unwrap();
+        let only_a_id = *ents.component_entity_map[&A::UUID]
+            .iter()
+            .find(|x| **x != both_id)
+            .unwrap();
+        assert!(ents.delete_entity(&both_id));
+
+        // systems still find it this frame
+        assert!(ents
+            .get_by_component_delete::<B>()
+            .unwrap()
+            .contains_key(&both_id));
+        ents.clear_deleted();
+
+        assert_eq!(
+            ents.component_entity_map[&A::UUID],
+            BTreeSet::from([only_a_id])
+        );
+        assert!(!ents.component_entity_map.contains_key(&B::UUID));
+        assert!(ents.all_delete().is_empty());
+    }
+}
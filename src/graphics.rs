@@ -5,7 +5,9 @@ use crate::entity_component::Component;
 
 pub mod component;
 pub mod renderer;
+mod shader_include;
 pub mod system;
+mod watcher;
 
 pub use component::GraphicsComponent;
 pub use renderer::K9Renderer;
@@ -4,11 +4,14 @@ use crate::{camera::ScreenCamera, entity_component::Component};
 
 use super::system::GraphicsCommandInterface;
 
+pub mod grid;
 pub mod texquad;
+pub use grid::GridGizmo;
 pub use texquad::TexQuadBase;
 
 pub enum GraphicsComponent {
     TexQuad(TexQuadBase),
+    Grid(GridGizmo),
 }
 impl GraphicsComponent {
     pub fn create(&mut self, k9cmd: &mut GraphicsCommandInterface, screen_camera: &ScreenCamera) {
@@ -20,16 +23,29 @@ impl GraphicsComponent {
     pub fn render(&mut self, k9cmd: &mut GraphicsCommandInterface, screen_camera: &ScreenCamera) {
         self.get_inner_mut().render(k9cmd, screen_camera)
     }
+    pub fn set_world_transform(&mut self, world: glam::Mat4) {
+        self.get_inner_mut().set_world_transform(world)
+    }
+    pub fn render_pick(
+        &mut self,
+        k9cmd: &mut GraphicsCommandInterface,
+        screen_camera: &ScreenCamera,
+        pick: &PickPass,
+    ) {
+        self.get_inner_mut().render_pick(k9cmd, screen_camera, pick)
+    }
 
     pub fn get_inner(&self) -> &dyn GraphicsComponentImpl {
         match self {
             Self::TexQuad(base) => base as &dyn GraphicsComponentImpl,
+            Self::Grid(grid) => grid as &dyn GraphicsComponentImpl,
         }
     }
 
     pub fn get_inner_mut(&mut self) -> &mut dyn GraphicsComponentImpl {
         match self {
             Self::TexQuad(base) => base as &mut dyn GraphicsComponentImpl,
+            Self::Grid(grid) => grid as &mut dyn GraphicsComponentImpl,
         }
     }
 }
@@ -42,6 +58,30 @@ pub trait GraphicsComponentImpl {
     fn create(&mut self, _k9cmd: &mut GraphicsCommandInterface, _screen_camera: &ScreenCamera) {}
     fn delete(&mut self, _k9cmd: &mut GraphicsCommandInterface, _screen_camera: &ScreenCamera) {}
     fn render(&mut self, k9cmd: &mut GraphicsCommandInterface, screen_camera: &ScreenCamera);
+    // the entity's world transform from its TransformComponent, set before each render for
+    // entities that have one
+    fn set_world_transform(&mut self, _world: glam::Mat4) {}
+    // draws the component's shape into the pick target, the pick program and colour are already
+    // bound so only geometry and the transform need uploading. components that don't implement
+    // this can't be picked.
+    fn render_pick(
+        &mut self,
+        _k9cmd: &mut GraphicsCommandInterface,
+        _screen_camera: &ScreenCamera,
+        _pick: &PickPass,
+    ) {
+    }
+}
+
+// handed to render_pick while the pick pass is being drawn
+pub struct PickPass {
+    pub(crate) u_transform: Uuid,
+}
+impl PickPass {
+    // mat4 uniform of the pick program
+    pub fn transform_uniform(&self) -> Uuid {
+        self.u_transform
+    }
 }
 
 pub enum RenderLocation {
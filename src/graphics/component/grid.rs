@@ -0,0 +1,137 @@
+use uuid::Uuid;
+
+use crate::{
+    camera::ScreenCamera,
+    graphics::{
+        system::{BuiltInShader, GraphicsCommandInterface},
+        Vertex,
+    },
+    shaders,
+};
+
+use super::GraphicsComponentImpl;
+
+const GRID_COLOUR: glam::Vec4 = glam::Vec4::new(0.6, 0.6, 0.6, 0.5);
+const AXIS_COLOURS: [glam::Vec4; 3] = [
+    glam::Vec4::new(0.9, 0.2, 0.2, 1.0), // x
+    glam::Vec4::new(0.2, 0.9, 0.2, 1.0), // y
+    glam::Vec4::new(0.2, 0.4, 0.9, 1.0), // z
+];
+// keeps the grid's vertex count within u16 indices
+const MAX_HALF_CELLS: u32 = 1_000;
+
+// ground grid on the xz plane centered on the origin, with x/y/z axis lines drawn over it
+pub struct GridGizmo {
+    half_cells: u32,
+    spacing: f32,
+    core: Option<GridGizmoCore>,
+}
+struct GridGizmoCore {
+    grid_src: Uuid,
+    grid_count: u32,
+    axis_srcs: [Uuid; 3],
+    sh_vert: Uuid,
+    sh_frag: Uuid,
+    program: Uuid,
+    u_transform: Uuid,
+    u_colour: Uuid,
+}
+impl GridGizmo {
+    // half_cells is the number of cells from the origin to each edge, spacing is a cell's width
+    pub fn new(half_cells: u32, spacing: f32) -> Self {
+        if half_cells > MAX_HALF_CELLS {
+            log::warn!("grid half cells {half_cells} clamped to {MAX_HALF_CELLS}");
+        }
+
+        Self {
+            half_cells: half_cells.min(MAX_HALF_CELLS),
+            spacing,
+            core: None,
+        }
+    }
+}
+impl Default for GridGizmo {
+    fn default() -> Self {
+        Self::new(20, 50.0)
+    }
+}
+impl GraphicsComponentImpl for GridGizmo {
+    fn create(&mut self, k9cmd: &mut GraphicsCommandInterface, _screen_camera: &ScreenCamera) {
+        let n = self.half_cells as i32;
+        let extent = self.half_cells as f32 * self.spacing;
+        let point = |x: f32, y: f32, z: f32| Vertex {
+            x,
+            y,
+            z,
+            u: 0.0,
+            v: 0.0,
+        };
+
+        let mut vertices = Vec::new();
+        for i in -n..=n {
+            let offset = i as f32 * self.spacing;
+            vertices.push(point(-extent, 0.0, offset));
+            vertices.push(point(extent, 0.0, offset));
+            vertices.push(point(offset, 0.0, -extent));
+            vertices.push(point(offset, 0.0, extent));
+        }
+        let indices: Vec<u16> = (0..vertices.len() as u16).collect();
+        let grid_count = indices.len() as u32;
+        let grid_src = k9cmd.create_vertex_source(vertices, indices);
+
+        let axis_ends = [
+            point(extent, 0.0, 0.0),
+            point(0.0, extent, 0.0),
+            point(0.0, 0.0, extent),
+        ];
+        let axis_srcs = axis_ends.map(|end| {
+            k9cmd.create_vertex_source([point(0.0, 0.0, 0.0), end].to_vec(), [0, 1].to_vec())
+        });
+
+        let sh_vert = k9cmd.create_shader_builtin(BuiltInShader::LineVert);
+        let sh_frag = k9cmd.create_shader_builtin(BuiltInShader::LineFrag);
+        let program = k9cmd.create_shader_program([sh_vert, sh_frag].to_vec());
+        let u_transform = k9cmd.create_uniform_link(program, shaders::LINE_TRANSFORM);
+        let u_colour = k9cmd.create_uniform_link(program, shaders::LINE_COLOUR);
+
+        self.core = Some(GridGizmoCore {
+            grid_src,
+            grid_count,
+            axis_srcs,
+            sh_vert,
+            sh_frag,
+            program,
+            u_transform,
+            u_colour,
+        })
+    }
+
+    fn render(&mut self, k9cmd: &mut GraphicsCommandInterface, screen_camera: &ScreenCamera) {
+        if let Some(core) = &self.core {
+            k9cmd.use_shader_program(core.program);
+            k9cmd.upload_uniform_mat4(core.u_transform, screen_camera.view_proj_matrix());
+
+            k9cmd.bind_vertex_source(core.grid_src);
+            k9cmd.upload_uniform_vec4(core.u_colour, GRID_COLOUR);
+            k9cmd.draw_lines(core.grid_count);
+
+            for (axis_src, colour) in core.axis_srcs.iter().zip(AXIS_COLOURS) {
+                k9cmd.bind_vertex_source(*axis_src);
+                k9cmd.upload_uniform_vec4(core.u_colour, colour);
+                k9cmd.draw_lines(2);
+            }
+        }
+    }
+
+    fn delete(&mut self, k9cmd: &mut GraphicsCommandInterface, _screen_camera: &ScreenCamera) {
+        if let Some(core) = self.core.take() {
+            k9cmd.delete_shader_program(core.program);
+            k9cmd.delete_shader(core.sh_frag);
+            k9cmd.delete_shader(core.sh_vert);
+            for axis_src in core.axis_srcs {
+                k9cmd.delete_vertex_source(axis_src);
+            }
+            k9cmd.delete_vertex_source(core.grid_src);
+        }
+    }
+}
@@ -5,17 +5,23 @@ use uuid::Uuid;
 use crate::{
     camera::ScreenCamera,
     graphics::{
-        system::{BuiltInShader, GraphicsCommandInterface},
+        system::{AlphaMode, BuiltInShader, GraphicsCommandInterface},
         Vertex,
     },
+    shaders,
 };
 
-use super::{GraphicsComponentImpl, RenderLocation};
+use super::{GraphicsComponentImpl, PickPass, RenderLocation};
 
 pub struct TexQuadBase {
     vdimensions: (f32, f32),
     location: RenderLocation,
     texture_path: PathBuf,
+    uv_min: (f32, f32),
+    uv_max: (f32, f32),
+    // Some loads the texture as rgba8, None keeps the default rgb8 which drops alpha
+    alpha_mode: Option<AlphaMode>,
+    world: glam::Mat4,
     core: Option<TexQuadCore>,
 }
 struct TexQuadCore {
@@ -27,47 +33,105 @@ struct TexQuadCore {
     u_transform: Uuid,
 }
 impl TexQuadBase {
-    pub fn new() -> Self {
+    pub fn new(texture_path: impl Into<PathBuf>) -> Self {
         Self {
             vdimensions: (200.0, 200.0),
             location: RenderLocation::Screen(0.0, 0.0, 0.0),
-            texture_path: PathBuf::from("assets/textures/test_squeezel.png"),
+            texture_path: texture_path.into(),
+            uv_min: (0.0, 0.0),
+            uv_max: (1.0, 1.0),
+            alpha_mode: None,
+            world: glam::Mat4::IDENTITY,
             core: None,
         }
     }
+
+    // draws only the given region of the texture, uvs are normalized with 0,0 at the top left
+    pub fn with_uv_rect(
+        texture_path: impl Into<PathBuf>,
+        uv_min: (f32, f32),
+        uv_max: (f32, f32),
+    ) -> Result<Self, String> {
+        let in_range = |v: f32| (0.0..=1.0).contains(&v);
+        if !in_range(uv_min.0) || !in_range(uv_min.1) || !in_range(uv_max.0) || !in_range(uv_max.1)
+        {
+            return Err(format!(
+                "uv rect {uv_min:?}..{uv_max:?} must be within 0..1"
+            ));
+        }
+        if uv_min.0 >= uv_max.0 || uv_min.1 >= uv_max.1 {
+            return Err(format!(
+                "uv rect min {uv_min:?} must be less than max {uv_max:?}"
+            ));
+        }
+
+        let mut rval = Self::new(texture_path);
+        rval.uv_min = uv_min;
+        rval.uv_max = uv_max;
+        Ok(rval)
+    }
+
+    // same as with_uv_rect but in pixels of a texture with the given dimensions
+    pub fn with_uv_rect_px(
+        texture_path: impl Into<PathBuf>,
+        px_min: (u32, u32),
+        px_max: (u32, u32),
+        texture_dimensions: (u32, u32),
+    ) -> Result<Self, String> {
+        if texture_dimensions.0 == 0 || texture_dimensions.1 == 0 {
+            return Err(format!(
+                "invalid texture dimensions: {texture_dimensions:?}"
+            ));
+        }
+        let w = texture_dimensions.0 as f32;
+        let h = texture_dimensions.1 as f32;
+        Self::with_uv_rect(
+            texture_path,
+            (px_min.0 as f32 / w, px_min.1 as f32 / h),
+            (px_max.0 as f32 / w, px_max.1 as f32 / h),
+        )
+    }
+
+    // keeps the texture's alpha channel, blended according to alpha_mode
+    pub fn with_alpha(mut self, alpha_mode: AlphaMode) -> Self {
+        self.alpha_mode = Some(alpha_mode);
+        self
+    }
 }
 impl GraphicsComponentImpl for TexQuadBase {
     fn create(&mut self, k9cmd: &mut GraphicsCommandInterface, _screen_camera: &ScreenCamera) {
         let w2 = self.vdimensions.0 / 2.0;
         let h2 = self.vdimensions.1 / 2.0;
+        let (u0, v0) = self.uv_min;
+        let (u1, v1) = self.uv_max;
         let vertices: Vec<Vertex> = [
             Vertex {
                 x: -w2,
                 y: h2,
                 z: 0.0,
-                u: 0.0,
-                v: 0.0,
+                u: u0,
+                v: v0,
             }, // tl
             Vertex {
                 x: w2,
                 y: h2,
                 z: 0.0,
-                u: 1.0,
-                v: 0.0,
+                u: u1,
+                v: v0,
             }, // tr
             Vertex {
                 x: w2,
                 y: -h2,
                 z: 0.0,
-                u: 1.0,
-                v: 1.0,
+                u: u1,
+                v: v1,
             }, // br
             Vertex {
                 x: -w2,
                 y: -h2,
                 z: 0.0,
-                u: 0.0,
-                v: 1.0,
+                u: u0,
+                v: v1,
             }, // bl
         ]
         .into_iter()
@@ -75,11 +139,14 @@ impl GraphicsComponentImpl for TexQuadBase {
         let indices: Vec<u16> = [0, 1, 2, 0, 2, 3].into_iter().collect();
 
         let vert_src = k9cmd.create_vertex_source(vertices, indices);
-        let tex = k9cmd.create_texture_rgb8(self.texture_path.clone());
+        let tex = match self.alpha_mode {
+            Some(alpha_mode) => k9cmd.create_texture_rgba8(self.texture_path.clone(), alpha_mode),
+            None => k9cmd.create_texture_rgb8(self.texture_path.clone()),
+        };
         let sh_vert = k9cmd.create_shader_builtin(BuiltInShader::TexQuadVert);
         let sh_frag = k9cmd.create_shader_builtin(BuiltInShader::TexQuadFrag);
         let program = k9cmd.create_shader_program([sh_vert, sh_frag].to_vec());
-        let u_transform = k9cmd.create_uniform_link(program, "transform");
+        let u_transform = k9cmd.create_uniform_link(program, shaders::TEXQUAD_TRANSFORM);
 
         self.core = Some(TexQuadCore {
             vert_src,
@@ -97,12 +164,35 @@ impl GraphicsComponentImpl for TexQuadBase {
             k9cmd.bind_vertex_source(core.vert_src);
             k9cmd.bind_texture(core.tex, 0);
 
-            k9cmd.upload_uniform_mat4(core.u_transform, screen_camera.view_proj_matrix());
+            k9cmd.upload_uniform_mat4(
+                core.u_transform,
+                screen_camera.view_proj_matrix() * self.world,
+            );
 
             k9cmd.draw_elements(6);
         }
     }
 
+    fn render_pick(
+        &mut self,
+        k9cmd: &mut GraphicsCommandInterface,
+        screen_camera: &ScreenCamera,
+        pick: &PickPass,
+    ) {
+        if let Some(core) = &self.core {
+            k9cmd.bind_vertex_source(core.vert_src);
+            k9cmd.upload_uniform_mat4(
+                pick.transform_uniform(),
+                screen_camera.view_proj_matrix() * self.world,
+            );
+            k9cmd.draw_elements(6);
+        }
+    }
+
+    fn set_world_transform(&mut self, world: glam::Mat4) {
+        self.world = world;
+    }
+
     fn delete(&mut self, k9cmd: &mut GraphicsCommandInterface, _screen_camera: &ScreenCamera) {
         if let Some(core) = &self.core {
             k9cmd.delete_shader_program(core.program);
@@ -4,34 +4,343 @@ use glow::HasContext;
 use std::fmt::Display;
 use uuid::Uuid;
 
-use super::{system::ShaderType, Vertex};
+use super::{
+    system::{
+        AlphaMode, ShaderType, StencilFunction, StencilOperation, TextureCreateOptions, WrapMode,
+    },
+    Vertex,
+};
+use crate::shaders;
 
 pub struct K9Renderer {
     vertex_sources: BTreeMap<Uuid, VertexSource>,
     texture_sources: BTreeMap<Uuid, glow::NativeTexture>,
+    texture_alpha_modes: BTreeMap<Uuid, AlphaMode>,
     shader_sources: BTreeMap<Uuid, glow::NativeShader>,
     shader_program_sources: BTreeMap<Uuid, glow::NativeProgram>,
     uniform_links: BTreeMap<Uuid, glow::NativeUniformLocation>,
+    // what each program was linked from and each uniform link was made with, so a reloaded shader
+    // can relink its programs and find their uniforms again
+    program_shaders: BTreeMap<Uuid, Vec<Uuid>>,
+    uniform_link_names: BTreeMap<Uuid, (Uuid, String)>,
+    max_texture_units: u32,
+    // what each texture unit is known to hold this frame, None when unbound or unknown. it's
+    // forgotten at the start of each frame since the debug ui binds textures too.
+    bound_textures: Vec<Option<glow::NativeTexture>>,
+    // current program and vao, tracked and forgotten the same way as textures
+    bound_program: Option<glow::NativeProgram>,
+    bound_vao: Option<glow::NativeVertexArray>,
+    line_width_range: (f32, f32),
+    warned_line_width: bool,
+    // offscreen target for pick passes, resized to the window when a pass asks for a new size
+    pick_target: Option<PickTarget>,
+    pick_results: Vec<u32>,
+    // viewport and stencil test to restore once a pick pass ends
+    pick_saved_viewport: [i32; 4],
+    pick_saved_stencil_test: bool,
+    // set between BeginPickPass and EndPickPass
+    in_pick_pass: bool,
+    // set when the pick target was bound for the current pass. when it couldn't be the pass's
+    // draws are skipped rather than landing in whatever was bound, and its reads are misses.
+    pick_pass_active: bool,
+    framebuffers: BTreeMap<Uuid, Framebuffer>,
+    // the framebuffer draws are going to, None for the window
+    bound_framebuffer: Option<Uuid>,
+    // window viewport to restore once a framebuffer is unbound
+    framebuffer_saved_viewport: [i32; 4],
+}
+
+// a render target, its colour texture is also in texture_sources under its texture id
+struct Framebuffer {
+    fbo: glow::NativeFramebuffer,
+    color: Option<(Uuid, glow::NativeTexture)>,
+    depth: Option<glow::NativeTexture>,
+    dimensions: (u32, u32),
+}
+
+struct PickTarget {
+    fbo: glow::NativeFramebuffer,
+    texture: glow::NativeTexture,
+    dimensions: (u32, u32),
 }
 
 impl K9Renderer {
-    pub fn new() -> Result<Self, String> {
+    pub fn new(glow: &glow::Context) -> Result<Self, String> {
+        let max_texture_units =
+            unsafe { glow.get_parameter_i32(glow::MAX_COMBINED_TEXTURE_IMAGE_UNITS) };
+        if max_texture_units <= 0 {
+            return Err(format!(
+                "invalid max combined texture image units: {max_texture_units}"
+            ));
+        }
+
+        let mut line_width_range = [1.0; 2];
+        unsafe {
+            glow.get_parameter_f32_slice(glow::ALIASED_LINE_WIDTH_RANGE, &mut line_width_range);
+        }
+
         Ok(Self {
             vertex_sources: BTreeMap::new(),
             texture_sources: BTreeMap::new(),
+            texture_alpha_modes: BTreeMap::new(),
             shader_sources: BTreeMap::new(),
             shader_program_sources: BTreeMap::new(),
             uniform_links: BTreeMap::new(),
+            program_shaders: BTreeMap::new(),
+            uniform_link_names: BTreeMap::new(),
+            max_texture_units: max_texture_units as u32,
+            bound_textures: vec![None; max_texture_units as usize],
+            bound_program: None,
+            bound_vao: None,
+            line_width_range: (line_width_range[0], line_width_range[1]),
+            warned_line_width: false,
+            pick_target: None,
+            pick_results: Vec::new(),
+            pick_saved_viewport: [0; 4],
+            pick_saved_stencil_test: false,
+            in_pick_pass: false,
+            pick_pass_active: false,
+            framebuffers: BTreeMap::new(),
+            bound_framebuffer: None,
+            framebuffer_saved_viewport: [0; 4],
         })
     }
 
-    pub fn render(&mut self, glow: &glow::Context, cmds: Vec<RenderCommand>) {
+    fn forget_texture_bindings(&mut self) {
+        self.bound_textures.fill(None);
+    }
+
+    fn forget_bindings(&mut self) {
+        self.forget_texture_bindings();
+        self.bound_program = None;
+        self.bound_vao = None;
+    }
+
+    // pick ids read back since the last call, in the order their passes were submitted
+    pub fn take_pick_results(&mut self) -> Vec<u32> {
+        std::mem::take(&mut self.pick_results)
+    }
+
+    unsafe fn bind_pick_target(
+        &mut self,
+        glow: &glow::Context,
+        dimensions: (u32, u32),
+    ) -> Result<(), String> {
+        if let Some(target) = &self.pick_target {
+            if target.dimensions == dimensions {
+                glow.bind_framebuffer(glow::FRAMEBUFFER, Some(target.fbo));
+                return Ok(());
+            }
+        }
+        if let Some(old) = self.pick_target.take() {
+            glow.delete_framebuffer(old.fbo);
+            glow.delete_texture(old.texture);
+        }
+
+        let texture = glow.create_texture()?;
+        glow.bind_texture(glow::TEXTURE_2D, Some(texture));
+        self.forget_texture_bindings();
+        glow.tex_image_2d(
+            glow::TEXTURE_2D,
+            0,
+            glow::RGBA8 as i32,
+            dimensions.0 as i32,
+            dimensions.1 as i32,
+            0,
+            glow::RGBA,
+            glow::UNSIGNED_BYTE,
+            None,
+        );
+        glow.tex_parameter_i32(
+            glow::TEXTURE_2D,
+            glow::TEXTURE_MIN_FILTER,
+            glow::NEAREST as i32,
+        );
+        glow.tex_parameter_i32(
+            glow::TEXTURE_2D,
+            glow::TEXTURE_MAG_FILTER,
+            glow::NEAREST as i32,
+        );
+
+        let fbo = match glow.create_framebuffer() {
+            Ok(x) => x,
+            Err(e) => {
+                glow.delete_texture(texture);
+                return Err(e);
+            }
+        };
+        glow.bind_framebuffer(glow::FRAMEBUFFER, Some(fbo));
+        glow.framebuffer_texture_2d(
+            glow::FRAMEBUFFER,
+            glow::COLOR_ATTACHMENT0,
+            glow::TEXTURE_2D,
+            Some(texture),
+            0,
+        );
+        let status = glow.check_framebuffer_status(glow::FRAMEBUFFER);
+        if status != glow::FRAMEBUFFER_COMPLETE {
+            glow.bind_framebuffer(glow::FRAMEBUFFER, None);
+            glow.delete_framebuffer(fbo);
+            glow.delete_texture(texture);
+            return Err(format!("pick framebuffer incomplete, status: 0x{status:x}"));
+        }
+
+        self.pick_target = Some(PickTarget {
+            fbo,
+            texture,
+            dimensions,
+        });
+        Ok(())
+    }
+
+    unsafe fn create_framebuffer(
+        &mut self,
+        glow: &glow::Context,
+        dimensions: (u32, u32),
+        color_attachment: Option<Uuid>,
+        depth_attachment: bool,
+    ) -> Result<Framebuffer, String> {
+        if dimensions.0 == 0 || dimensions.1 == 0 {
+            return Err(format!("framebuffer can't be {}x{}", dimensions.0, dimensions.1));
+        }
+        let (width, height) = (dimensions.0 as i32, dimensions.1 as i32);
+
+        let fbo = glow.create_framebuffer()?;
+        let mut framebuffer = Framebuffer {
+            fbo,
+            color: None,
+            depth: None,
+            dimensions,
+        };
+        // anything made before an error is deleted with the framebuffer
+        let delete = |framebuffer: Framebuffer| {
+            glow.bind_framebuffer(glow::FRAMEBUFFER, None);
+            glow.delete_framebuffer(framebuffer.fbo);
+            for texture in framebuffer.color.map(|x| x.1).into_iter().chain(framebuffer.depth) {
+                glow.delete_texture(texture);
+            }
+        };
+
+        glow.bind_framebuffer(glow::FRAMEBUFFER, Some(fbo));
+        // the attachments are bound to whichever unit is active while they're set up
+        self.forget_texture_bindings();
+
+        if let Some(id) = color_attachment {
+            let texture = match glow.create_texture() {
+                Ok(x) => x,
+                Err(e) => {
+                    delete(framebuffer);
+                    return Err(e);
+                }
+            };
+            framebuffer.color = Some((id, texture));
+            glow.bind_texture(glow::TEXTURE_2D, Some(texture));
+            glow.tex_image_2d(
+                glow::TEXTURE_2D,
+                0,
+                glow::RGBA8 as i32,
+                width,
+                height,
+                0,
+                glow::RGBA,
+                glow::UNSIGNED_BYTE,
+                None,
+            );
+            set_texture_options(
+                glow,
+                TextureCreateOptions {
+                    wrap_s: WrapMode::ClampToEdge,
+                    wrap_t: WrapMode::ClampToEdge,
+                    ..Default::default()
+                },
+            );
+            glow.framebuffer_texture_2d(
+                glow::FRAMEBUFFER,
+                glow::COLOR_ATTACHMENT0,
+                glow::TEXTURE_2D,
+                Some(texture),
+                0,
+            );
+        } else {
+            // depth only, e.g. a shadow map
+            glow.draw_buffer(glow::NONE);
+            glow.read_buffer(glow::NONE);
+        }
+
+        if depth_attachment {
+            let texture = match glow.create_texture() {
+                Ok(x) => x,
+                Err(e) => {
+                    delete(framebuffer);
+                    return Err(e);
+                }
+            };
+            framebuffer.depth = Some(texture);
+            glow.bind_texture(glow::TEXTURE_2D, Some(texture));
+            glow.tex_image_2d(
+                glow::TEXTURE_2D,
+                0,
+                glow::DEPTH_COMPONENT24 as i32,
+                width,
+                height,
+                0,
+                glow::DEPTH_COMPONENT,
+                glow::UNSIGNED_INT,
+                None,
+            );
+            set_texture_options(
+                glow,
+                TextureCreateOptions {
+                    mag_filter: egui::TextureFilter::Nearest,
+                    min_filter: egui::TextureFilter::Nearest,
+                    wrap_s: WrapMode::ClampToEdge,
+                    wrap_t: WrapMode::ClampToEdge,
+                },
+            );
+            glow.framebuffer_texture_2d(
+                glow::FRAMEBUFFER,
+                glow::DEPTH_ATTACHMENT,
+                glow::TEXTURE_2D,
+                Some(texture),
+                0,
+            );
+        }
+
+        let status = glow.check_framebuffer_status(glow::FRAMEBUFFER);
+        if status != glow::FRAMEBUFFER_COMPLETE {
+            delete(framebuffer);
+            return Err(format!("framebuffer incomplete, status: 0x{status:x}"));
+        }
+
+        // creating doesn't change where draws go
+        let bound = self
+            .bound_framebuffer
+            .and_then(|x| self.framebuffers.get(&x))
+            .map(|x| x.fbo);
+        glow.bind_framebuffer(glow::FRAMEBUFFER, bound);
+        Ok(framebuffer)
+    }
+
+    unsafe fn unbind_framebuffer(&mut self, glow: &glow::Context) {
+        if self.bound_framebuffer.take().is_some() {
+            let [x, y, w, h] = self.framebuffer_saved_viewport;
+            glow.bind_framebuffer(glow::FRAMEBUFFER, None);
+            glow.viewport(x, y, w, h);
+        }
+    }
+
+    // drains cmds, leaving the buffer empty so its allocation can be reused
+    pub fn render(&mut self, glow: &glow::Context, cmds: &mut Vec<RenderCommand>) {
         // draw code
         unsafe {
             glow.clear_color(0.2, 0.3, 0.3, 1.0);
             glow.clear(glow::COLOR_BUFFER_BIT);
+            glow.enable(glow::BLEND);
+            glow.blend_equation(glow::FUNC_ADD);
+            set_blend_func(glow, AlphaMode::Straight);
+            self.forget_bindings();
 
-            'render_command_loop: for cmd in cmds {
+            'render_command_loop: for cmd in cmds.drain(..) {
                 match cmd {
                     RenderCommand::CreateVertexSource {
                         id,
@@ -51,6 +360,7 @@ impl K9Renderer {
                             }
                         };
                         glow.bind_vertex_array(Some(vao));
+                        self.bound_vao = Some(vao);
 
                         let vbo = match glow.create_buffer() {
                             Ok(x) => x,
@@ -80,24 +390,33 @@ impl K9Renderer {
                             glow::STATIC_DRAW,
                         );
 
-                        glow.vertex_attrib_pointer_f32(0, 3, glow::FLOAT, false, 20, 0);
-                        glow.enable_vertex_attrib_array(0);
+                        let (pos, _) = shaders::VERTEX_POS;
+                        glow.vertex_attrib_pointer_f32(pos, 3, glow::FLOAT, false, 20, 0);
+                        glow.enable_vertex_attrib_array(pos);
 
-                        glow.vertex_attrib_pointer_f32(1, 2, glow::FLOAT, false, 20, 12);
-                        glow.enable_vertex_attrib_array(1);
+                        let (uv, _) = shaders::VERTEX_UV;
+                        glow.vertex_attrib_pointer_f32(uv, 2, glow::FLOAT, false, 20, 12);
+                        glow.enable_vertex_attrib_array(uv);
 
                         let vert_src = VertexSource { ebo, vao, vbo };
                         self.vertex_sources.insert(id, vert_src);
                     }
                     RenderCommand::BindVertexSource { id } => {
                         if let Some(vert_src) = self.vertex_sources.get(&id) {
-                            glow.bind_vertex_array(Some(vert_src.vao));
+                            if self.bound_vao != Some(vert_src.vao) {
+                                glow.bind_vertex_array(Some(vert_src.vao));
+                                self.bound_vao = Some(vert_src.vao);
+                            }
                         } else {
                             log::error!("bind couldn't find vertex source with id: {id}");
                         }
                     }
                     RenderCommand::DeleteVertexSource { id } => {
                         if let Some(vert_src) = self.vertex_sources.remove(&id) {
+                            // deleting the bound vao reverts the binding to zero
+                            if self.bound_vao == Some(vert_src.vao) {
+                                self.bound_vao = None;
+                            }
                             glow.delete_vertex_array(vert_src.vao);
                             glow.delete_buffer(vert_src.vbo);
                             glow.delete_buffer(vert_src.ebo);
@@ -109,6 +428,7 @@ impl K9Renderer {
                         id,
                         pixels,
                         dimensions,
+                        options,
                     } => {
                         if self.texture_sources.contains_key(&id) {
                             log::error!(
@@ -125,6 +445,7 @@ impl K9Renderer {
                             }
                         };
                         glow.bind_texture(glow::TEXTURE_2D, Some(tex));
+                        set_texture_options(glow, options);
                         glow.tex_image_2d(
                             glow::TEXTURE_2D,
                             0,
@@ -136,20 +457,101 @@ impl K9Renderer {
                             glow::UNSIGNED_BYTE,
                             Some(pixels.as_slice()),
                         );
+                        // bound to whichever unit was active
+                        self.forget_texture_bindings();
                         self.texture_sources.insert(id, tex);
                     }
+                    RenderCommand::CreateTextureRGBA8 {
+                        id,
+                        pixels,
+                        dimensions,
+                        alpha_mode,
+                        options,
+                    } => {
+                        if self.texture_sources.contains_key(&id) {
+                            log::error!(
+                                "request for unique texture source with duplicate id: {id}"
+                            );
+                            continue;
+                        }
+
+                        let tex = match glow.create_texture() {
+                            Ok(x) => x,
+                            Err(e) => {
+                                log::error!("couldn't create texture: {e}");
+                                continue;
+                            }
+                        };
+                        glow.bind_texture(glow::TEXTURE_2D, Some(tex));
+                        set_texture_options(glow, options);
+                        glow.tex_image_2d(
+                            glow::TEXTURE_2D,
+                            0,
+                            glow::RGBA8 as i32,
+                            dimensions.0,
+                            dimensions.1,
+                            0,
+                            glow::RGBA,
+                            glow::UNSIGNED_BYTE,
+                            Some(pixels.as_slice()),
+                        );
+                        // bound to whichever unit was active
+                        self.forget_texture_bindings();
+                        self.texture_sources.insert(id, tex);
+                        self.texture_alpha_modes.insert(id, alpha_mode);
+                    }
                     RenderCommand::BindTexture { id, texture_slot } => {
+                        if texture_slot as u32 >= self.max_texture_units {
+                            log::error!(
+                                "texture slot {texture_slot} out of range, max texture units: {}",
+                                self.max_texture_units
+                            );
+                            continue;
+                        }
+
                         if let Some(tex) = self.texture_sources.get(&id) {
-                            glow.active_texture(glow::TEXTURE0 + texture_slot as u32);
-                            glow.bind_texture(glow::TEXTURE_2D, Some(*tex));
+                            let bound = &mut self.bound_textures[texture_slot as usize];
+                            if *bound != Some(*tex) {
+                                glow.active_texture(glow::TEXTURE0 + texture_slot as u32);
+                                glow.bind_texture(glow::TEXTURE_2D, Some(*tex));
+                                *bound = Some(*tex);
+                            }
+
+                            // the blend func follows the alpha mode of the last bound texture
+                            let alpha_mode = self
+                                .texture_alpha_modes
+                                .get(&id)
+                                .copied()
+                                .unwrap_or(AlphaMode::Straight);
+                            set_blend_func(glow, alpha_mode);
                         } else {
                             log::error!("couldn't find texture to bind with id: {id}");
                             continue;
                         }
                     }
+                    RenderCommand::UnbindTexture { texture_slot } => {
+                        if texture_slot as u32 >= self.max_texture_units {
+                            log::error!(
+                                "texture slot {texture_slot} out of range, max texture units: {}",
+                                self.max_texture_units
+                            );
+                            continue;
+                        }
+
+                        glow.active_texture(glow::TEXTURE0 + texture_slot as u32);
+                        glow.bind_texture(glow::TEXTURE_2D, None);
+                        self.bound_textures[texture_slot as usize] = None;
+                    }
                     RenderCommand::DeleteTexture { id } => {
                         if let Some(tex) = self.texture_sources.remove(&id) {
+                            // gl unbinds a deleted texture from every unit holding it
+                            for bound in &mut self.bound_textures {
+                                if *bound == Some(tex) {
+                                    *bound = None;
+                                }
+                            }
                             glow.delete_texture(tex);
+                            self.texture_alpha_modes.remove(&id);
                         } else {
                             log::error!("couldn't find texture to delete with id: {id}");
                         }
@@ -186,6 +588,85 @@ impl K9Renderer {
                             log::error!("couldn't find shader to delete with id: {id}");
                         }
                     }
+                    RenderCommand::ReloadShader {
+                        id,
+                        sh_type,
+                        source,
+                    } => {
+                        let old = match self.shader_sources.get(&id) {
+                            Some(x) => *x,
+                            None => {
+                                log::error!("couldn't find shader to reload with id: {id}");
+                                continue;
+                            }
+                        };
+
+                        let shader = match glow.create_shader(sh_type.into()) {
+                            Ok(x) => x,
+                            Err(e) => {
+                                log::error!("failed to create shader: {e}");
+                                continue;
+                            }
+                        };
+                        glow.shader_source(shader, &source);
+                        glow.compile_shader(shader);
+                        if !glow.get_shader_compile_status(shader) {
+                            let err = glow.get_shader_info_log(shader);
+                            glow.delete_shader(shader);
+                            log::error!("shader compile error, keeping the old shader: {err}");
+                            continue;
+                        }
+                        self.shader_sources.insert(id, shader);
+                        glow.delete_shader(old);
+
+                        // relinking in place keeps the program's name, but its uniform locations
+                        // can move
+                        for (program_id, shader_ids) in &self.program_shaders {
+                            if !shader_ids.contains(&id) {
+                                continue;
+                            }
+                            let program = match self.shader_program_sources.get(program_id) {
+                                Some(x) => *x,
+                                None => continue,
+                            };
+
+                            let shaders: Vec<glow::NativeShader> = shader_ids
+                                .iter()
+                                .filter_map(|x| self.shader_sources.get(x).copied())
+                                .collect();
+                            for shader in &shaders {
+                                glow.attach_shader(program, *shader);
+                            }
+                            glow.link_program(program);
+                            for shader in &shaders {
+                                glow.detach_shader(program, *shader);
+                            }
+                            if !glow.get_program_link_status(program) {
+                                let err = glow.get_program_info_log(program);
+                                log::error!(
+                                    "couldn't relink program with id '{program_id}': {err}"
+                                );
+                                continue;
+                            }
+
+                            for (uniform_id, (link_program_id, name)) in &self.uniform_link_names {
+                                if link_program_id != program_id {
+                                    continue;
+                                }
+                                match glow.get_uniform_location(program, name) {
+                                    Some(loc) => {
+                                        self.uniform_links.insert(*uniform_id, loc);
+                                    }
+                                    None => {
+                                        self.uniform_links.remove(uniform_id);
+                                        log::error!("uniform '{name}' is gone from program '{program_id}' after a reload");
+                                    }
+                                }
+                            }
+                        }
+                        // a relinked current program may need using again
+                        self.bound_program = None;
+                    }
                     RenderCommand::CreateShaderProgram { id, shader_ids } => {
                         let program = match glow.create_program() {
                             Ok(x) => x,
@@ -196,8 +677,8 @@ impl K9Renderer {
                         };
 
                         let mut shaders = Vec::new();
-                        for sh_id in shader_ids {
-                            let shader = match self.shader_sources.get(&sh_id) {
+                        for sh_id in &shader_ids {
+                            let shader = match self.shader_sources.get(sh_id) {
                                 Some(x) => x,
                                 None => {
                                     log::error!("couldn't get shader: {id}");
@@ -221,10 +702,16 @@ impl K9Renderer {
                         }
 
                         self.shader_program_sources.insert(id, program);
+                        self.program_shaders.insert(id, shader_ids);
                     }
                     RenderCommand::DeleteShaderProgram { id } => {
                         if let Some(program) = self.shader_program_sources.remove(&id) {
+                            // a deleted program's name can be reused, don't trust it as current
+                            if self.bound_program == Some(program) {
+                                self.bound_program = None;
+                            }
                             glow.delete_program(program);
+                            self.program_shaders.remove(&id);
                         } else {
                             log::error!("couldn't find shader program to delete with id: {id}");
                             continue;
@@ -232,12 +719,24 @@ impl K9Renderer {
                     }
                     RenderCommand::UseShaderProgram { id } => {
                         if let Some(program) = self.shader_program_sources.get(&id) {
-                            glow.use_program(Some(*program));
+                            if self.bound_program != Some(*program) {
+                                glow.use_program(Some(*program));
+                                self.bound_program = Some(*program);
+                            }
                         }
                     }
                     RenderCommand::DrawElements { count } => {
+                        if self.in_pick_pass && !self.pick_pass_active {
+                            continue;
+                        }
                         glow.draw_elements(glow::TRIANGLES, count as i32, glow::UNSIGNED_SHORT, 0);
                     }
+                    RenderCommand::DrawLines { count } => {
+                        if self.in_pick_pass && !self.pick_pass_active {
+                            continue;
+                        }
+                        glow.draw_elements(glow::LINES, count as i32, glow::UNSIGNED_SHORT, 0);
+                    }
                     RenderCommand::CreateUniformLink {
                         new_uniform_id,
                         existing_program_id,
@@ -254,6 +753,8 @@ impl K9Renderer {
                             };
 
                             self.uniform_links.insert(new_uniform_id, loc);
+                            self.uniform_link_names
+                                .insert(new_uniform_id, (existing_program_id, uniform_name));
                         } else {
                             log::error!("couldn't find shader program for CreateUniformLink with id: {existing_program_id}");
                             continue;
@@ -271,8 +772,244 @@ impl K9Renderer {
                             continue;
                         }
                     }
+                    RenderCommand::UploadUniformVec4 { id, data } => {
+                        if let Some(loc) = self.uniform_links.get(&id) {
+                            glow.uniform_4_f32_slice(Some(loc), &data.to_array());
+                        } else {
+                            log::error!("couldn't find uniform location by id: {id}");
+                            continue;
+                        }
+                    }
+                    RenderCommand::UploadUniformVec2 { id, data } => {
+                        if let Some(loc) = self.uniform_links.get(&id) {
+                            glow.uniform_2_f32_slice(Some(loc), &data.to_array());
+                        } else {
+                            log::error!("couldn't find uniform location by id: {id}");
+                            continue;
+                        }
+                    }
+                    RenderCommand::UploadUniformVec3 { id, data } => {
+                        if let Some(loc) = self.uniform_links.get(&id) {
+                            glow.uniform_3_f32_slice(Some(loc), &data.to_array());
+                        } else {
+                            log::error!("couldn't find uniform location by id: {id}");
+                            continue;
+                        }
+                    }
+                    RenderCommand::UploadUniformF32 { id, data } => {
+                        if let Some(loc) = self.uniform_links.get(&id) {
+                            glow.uniform_1_f32(Some(loc), data);
+                        } else {
+                            log::error!("couldn't find uniform location by id: {id}");
+                            continue;
+                        }
+                    }
+                    RenderCommand::UploadUniformI32 { id, data } => {
+                        if let Some(loc) = self.uniform_links.get(&id) {
+                            glow.uniform_1_i32(Some(loc), data);
+                        } else {
+                            log::error!("couldn't find uniform location by id: {id}");
+                            continue;
+                        }
+                    }
+                    RenderCommand::SetStencilTest { enabled } => {
+                        if enabled {
+                            glow.enable(glow::STENCIL_TEST);
+                        } else {
+                            glow.disable(glow::STENCIL_TEST);
+                        }
+                    }
+                    RenderCommand::StencilFunc {
+                        func,
+                        reference,
+                        mask,
+                    } => {
+                        glow.stencil_func(func.into(), reference, mask);
+                    }
+                    RenderCommand::StencilOp {
+                        stencil_fail,
+                        depth_fail,
+                        pass,
+                    } => {
+                        glow.stencil_op(stencil_fail.into(), depth_fail.into(), pass.into());
+                    }
+                    RenderCommand::ClearStencil { value } => {
+                        glow.stencil_mask(0xFF);
+                        glow.clear_stencil(value);
+                        glow.clear(glow::STENCIL_BUFFER_BIT);
+                    }
+                    RenderCommand::SetLineWidth { width } => {
+                        let (min, max) = self.line_width_range;
+                        let clamped = width.clamp(min, max);
+                        if clamped != width && !self.warned_line_width {
+                            self.warned_line_width = true;
+                            log::warn!("line width {width} unsupported, clamped to {clamped} (supported range: {min}..={max})");
+                        }
+                        glow.line_width(clamped);
+                    }
+                    RenderCommand::SetProgramPointSize { enabled } => {
+                        if enabled {
+                            glow.enable(glow::PROGRAM_POINT_SIZE);
+                        } else {
+                            glow.disable(glow::PROGRAM_POINT_SIZE);
+                        }
+                    }
+                    RenderCommand::SetAlphaToCoverage { enabled } => {
+                        if enabled {
+                            glow.enable(glow::SAMPLE_ALPHA_TO_COVERAGE);
+                        } else {
+                            glow.disable(glow::SAMPLE_ALPHA_TO_COVERAGE);
+                        }
+                    }
+                    RenderCommand::BeginPickPass { dimensions } => {
+                        // picking has no depth test, like the window draws. overlapping shapes
+                        // resolve by draw order, which is the same order the window is drawn in
+                        // so the pick matches what's on screen.
+                        self.in_pick_pass = true;
+                        self.pick_pass_active = false;
+                        if let Err(e) = self.bind_pick_target(glow, dimensions) {
+                            log::error!("couldn't create pick target: {e}");
+                            continue;
+                        }
+                        self.pick_pass_active = true;
+                        glow.get_parameter_i32_slice(glow::VIEWPORT, &mut self.pick_saved_viewport);
+                        self.pick_saved_stencil_test = glow.is_enabled(glow::STENCIL_TEST);
+                        glow.viewport(0, 0, dimensions.0 as i32, dimensions.1 as i32);
+                        glow.disable(glow::BLEND);
+                        glow.disable(glow::STENCIL_TEST);
+                        glow.clear_color(0.0, 0.0, 0.0, 0.0);
+                        glow.clear(glow::COLOR_BUFFER_BIT);
+                    }
+                    RenderCommand::ReadPickPixel { x, y } => {
+                        // a pass that couldn't bind its target or an out of bounds pixel reads
+                        // as a miss, a result is still pushed so results line up with passes
+                        let mut pixel = [0u8; 4];
+                        let target = self.pick_target.as_ref().filter(|_| self.pick_pass_active);
+                        if let Some(target) = target {
+                            let (w, h) = target.dimensions;
+                            if x >= 0 && y >= 0 && (x as u32) < w && (y as u32) < h {
+                                glow.read_pixels(
+                                    x,
+                                    y,
+                                    1,
+                                    1,
+                                    glow::RGBA,
+                                    glow::UNSIGNED_BYTE,
+                                    glow::PixelPackData::Slice(&mut pixel),
+                                );
+                            }
+                        }
+                        self.pick_results.push(
+                            pixel[0] as u32 | (pixel[1] as u32) << 8 | (pixel[2] as u32) << 16,
+                        );
+                    }
+                    RenderCommand::EndPickPass => {
+                        let active = self.pick_pass_active;
+                        self.in_pick_pass = false;
+                        self.pick_pass_active = false;
+                        if !active {
+                            // nothing was rebound, draws are still going where they were
+                            continue;
+                        }
+                        let [x, y, w, h] = self.pick_saved_viewport;
+                        // back to wherever draws were going before the pass
+                        let bound = self
+                            .bound_framebuffer
+                            .and_then(|x| self.framebuffers.get(&x))
+                            .map(|x| x.fbo);
+                        glow.bind_framebuffer(glow::FRAMEBUFFER, bound);
+                        glow.viewport(x, y, w, h);
+                        glow.enable(glow::BLEND);
+                        set_blend_func(glow, AlphaMode::Straight);
+                        if self.pick_saved_stencil_test {
+                            glow.enable(glow::STENCIL_TEST);
+                        }
+                    }
+                    RenderCommand::CreateFramebuffer {
+                        id,
+                        width,
+                        height,
+                        color_attachment,
+                        depth_attachment,
+                    } => {
+                        if self.framebuffers.contains_key(&id) {
+                            log::error!("request for unique framebuffer with duplicate id: {id}");
+                            continue;
+                        }
+
+                        match self.create_framebuffer(glow, (width, height), color_attachment, depth_attachment) {
+                            Ok(framebuffer) => {
+                                if let Some((texture_id, texture)) = framebuffer.color {
+                                    self.texture_sources.insert(texture_id, texture);
+                                }
+                                self.framebuffers.insert(id, framebuffer);
+                            }
+                            Err(e) => log::error!("couldn't create framebuffer: {e}"),
+                        }
+                    }
+                    RenderCommand::BindFramebuffer { id } => {
+                        let framebuffer = match self.framebuffers.get(&id) {
+                            Some(x) => x,
+                            None => {
+                                log::error!("couldn't find framebuffer to bind with id: {id}");
+                                continue;
+                            }
+                        };
+
+                        // only the window's viewport is saved, binding another framebuffer over
+                        // this one keeps it
+                        if self.bound_framebuffer.is_none() {
+                            glow.get_parameter_i32_slice(glow::VIEWPORT, &mut self.framebuffer_saved_viewport);
+                        }
+                        glow.bind_framebuffer(glow::FRAMEBUFFER, Some(framebuffer.fbo));
+                        glow.viewport(0, 0, framebuffer.dimensions.0 as i32, framebuffer.dimensions.1 as i32);
+                        self.bound_framebuffer = Some(id);
+                    }
+                    RenderCommand::ClearFramebuffer { colour } => {
+                        glow.clear_color(colour.x, colour.y, colour.z, colour.w);
+                        glow.clear(glow::COLOR_BUFFER_BIT | glow::DEPTH_BUFFER_BIT);
+                    }
+                    RenderCommand::UnbindFramebuffer => {
+                        self.unbind_framebuffer(glow);
+                    }
+                    RenderCommand::DeleteFramebuffer { id } => {
+                        let framebuffer = match self.framebuffers.remove(&id) {
+                            Some(x) => x,
+                            None => {
+                                log::error!("couldn't find framebuffer to delete with id: {id}");
+                                continue;
+                            }
+                        };
+
+                        if self.bound_framebuffer == Some(id) {
+                            self.unbind_framebuffer(glow);
+                        }
+                        glow.delete_framebuffer(framebuffer.fbo);
+                        // the colour texture could already be gone if it was deleted as a texture
+                        if let Some((texture_id, texture)) = framebuffer.color {
+                            if self.texture_sources.get(&texture_id) == Some(&texture) {
+                                self.texture_sources.remove(&texture_id);
+                                for bound in &mut self.bound_textures {
+                                    if *bound == Some(texture) {
+                                        *bound = None;
+                                    }
+                                }
+                                glow.delete_texture(texture);
+                            }
+                        }
+                        if let Some(texture) = framebuffer.depth {
+                            glow.delete_texture(texture);
+                        }
+                    }
                 }
             }
+
+            // the debug ui draws to the window
+            self.unbind_framebuffer(glow);
+
+            // stencil and coverage state must not carry over into the debug ui pass
+            glow.disable(glow::STENCIL_TEST);
+            glow.disable(glow::SAMPLE_ALPHA_TO_COVERAGE);
         }
     }
 }
@@ -293,11 +1030,22 @@ pub enum RenderCommand {
         id: Uuid,
         dimensions: (i32, i32),
         pixels: Vec<u8>,
+        options: TextureCreateOptions,
+    },
+    CreateTextureRGBA8 {
+        id: Uuid,
+        dimensions: (i32, i32),
+        pixels: Vec<u8>,
+        alpha_mode: AlphaMode,
+        options: TextureCreateOptions,
     },
     BindTexture {
         id: Uuid,
         texture_slot: u8,
     },
+    UnbindTexture {
+        texture_slot: u8,
+    },
     DeleteTexture {
         id: Uuid,
     },
@@ -309,6 +1057,12 @@ pub enum RenderCommand {
     DeleteShader {
         id: Uuid,
     },
+    // recompiles an existing shader and relinks the programs using it
+    ReloadShader {
+        id: Uuid,
+        sh_type: ShaderType,
+        source: String,
+    },
     CreateShaderProgram {
         id: Uuid,
         shader_ids: Vec<Uuid>,
@@ -322,6 +1076,9 @@ pub enum RenderCommand {
     DrawElements {
         count: u32,
     },
+    DrawLines {
+        count: u32,
+    },
     CreateUniformLink {
         new_uniform_id: Uuid,
         existing_program_id: Uuid,
@@ -331,6 +1088,76 @@ pub enum RenderCommand {
         id: Uuid,
         data: glam::Mat4,
     },
+    UploadUniformVec4 {
+        id: Uuid,
+        data: glam::Vec4,
+    },
+    UploadUniformVec2 {
+        id: Uuid,
+        data: glam::Vec2,
+    },
+    UploadUniformVec3 {
+        id: Uuid,
+        data: glam::Vec3,
+    },
+    UploadUniformF32 {
+        id: Uuid,
+        data: f32,
+    },
+    UploadUniformI32 {
+        id: Uuid,
+        data: i32,
+    },
+    SetStencilTest {
+        enabled: bool,
+    },
+    StencilFunc {
+        func: StencilFunction,
+        reference: i32,
+        mask: u32,
+    },
+    StencilOp {
+        stencil_fail: StencilOperation,
+        depth_fail: StencilOperation,
+        pass: StencilOperation,
+    },
+    ClearStencil {
+        value: i32,
+    },
+    SetLineWidth {
+        width: f32,
+    },
+    SetProgramPointSize {
+        enabled: bool,
+    },
+    SetAlphaToCoverage {
+        enabled: bool,
+    },
+    BeginPickPass {
+        dimensions: (u32, u32),
+    },
+    ReadPickPixel {
+        x: i32,
+        y: i32,
+    },
+    EndPickPass,
+    CreateFramebuffer {
+        id: Uuid,
+        width: u32,
+        height: u32,
+        color_attachment: Option<Uuid>,
+        depth_attachment: bool,
+    },
+    BindFramebuffer {
+        id: Uuid,
+    },
+    UnbindFramebuffer,
+    ClearFramebuffer {
+        colour: glam::Vec4,
+    },
+    DeleteFramebuffer {
+        id: Uuid,
+    },
 }
 impl Display for RenderCommand {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -339,17 +1166,41 @@ impl Display for RenderCommand {
             Self::CreateVertexSource { id, vertices, indices } => write!(f, "CreateVertexSource {{ id: {id}, {} vertices, {} indices }}", vertices.len(), indices.len()),
             Self::BindVertexSource { id } => write!(f, "BindVertexSource {{ id: {id} }}"),
             Self::DeleteVertexSource { id } => write!(f, "DeleteVertexSource {{ id: {id} }}"),
-            Self::CreateTextureRGB8 { id, dimensions, pixels } => write!(f, "CreateTextureRGB8 {{ id: {id}, {}x{}, {} bytes }}", dimensions.0, dimensions.1, pixels.len()),
+            Self::CreateTextureRGB8 { id, dimensions, pixels, options } => write!(f, "CreateTextureRGB8 {{ id: {id}, {}x{}, {} bytes, {options:?} }}", dimensions.0, dimensions.1, pixels.len()),
+            Self::CreateTextureRGBA8 { id, dimensions, pixels, alpha_mode, options } => write!(f, "CreateTextureRGBA8 {{ id: {id}, {}x{}, {} bytes, {alpha_mode:?}, {options:?} }}", dimensions.0, dimensions.1, pixels.len()),
             Self::BindTexture { id, texture_slot } => write!(f, "BindTexture {{ id: {id}, slot: {texture_slot} }}"),
+            Self::UnbindTexture { texture_slot } => write!(f, "UnbindTexture {{ slot: {texture_slot} }}"),
             Self::DeleteTexture { id } => write!(f, "DeleteTexture {{ id: {id} }}"),
             Self::CreateShader { id, sh_type, source } => write!(f, "CreateShader {{ id: {id}, shader_type: {sh_type:?}, {} byte source }}", source.len()),
             Self::DeleteShader { id } => write!(f, "DeleteShader {{ id: {id} }}"),
+            Self::ReloadShader { id, sh_type, source } => write!(f, "ReloadShader {{ id: {id}, shader_type: {sh_type:?}, {} byte source }}", source.len()),
             Self::CreateShaderProgram { id, shader_ids } => write!(f, "CreateShaderProgram {{ id: {id}, shader_ids: {shader_ids:?} }}"),
             Self::DeleteShaderProgram { id } => write!(f, "DeleteShaderProgram {{ id: {id} }}"),
             Self::UseShaderProgram { id } => write!(f, "UseShaderProgram {{ id: {id} }}"),
             Self::DrawElements{ count } => write!(f, "DrawElements{{ count: {count} }}"),
+            Self::DrawLines { count } => write!(f, "DrawLines {{ count: {count} }}"),
             Self::CreateUniformLink { new_uniform_id, existing_program_id, uniform_name } => write!(f, "CreateUniformLink {{ new_uniform_id: {new_uniform_id}, existing_program_id: {existing_program_id}, uniform_name: {uniform_name} }}"),
             Self::UploadUniformMat4 { id, data } => write!(f, "UploadUniformMat4 {{ id: {id}, data: {data} }}"),
+            Self::UploadUniformVec4 { id, data } => write!(f, "UploadUniformVec4 {{ id: {id}, data: {data} }}"),
+            Self::UploadUniformVec2 { id, data } => write!(f, "UploadUniformVec2 {{ id: {id}, data: {data} }}"),
+            Self::UploadUniformVec3 { id, data } => write!(f, "UploadUniformVec3 {{ id: {id}, data: {data} }}"),
+            Self::UploadUniformF32 { id, data } => write!(f, "UploadUniformF32 {{ id: {id}, data: {data} }}"),
+            Self::UploadUniformI32 { id, data } => write!(f, "UploadUniformI32 {{ id: {id}, data: {data} }}"),
+            Self::SetStencilTest { enabled } => write!(f, "SetStencilTest {{ enabled: {enabled} }}"),
+            Self::StencilFunc { func, reference, mask } => write!(f, "StencilFunc {{ func: {func:?}, reference: {reference}, mask: 0x{mask:x} }}"),
+            Self::StencilOp { stencil_fail, depth_fail, pass } => write!(f, "StencilOp {{ stencil_fail: {stencil_fail:?}, depth_fail: {depth_fail:?}, pass: {pass:?} }}"),
+            Self::ClearStencil { value } => write!(f, "ClearStencil {{ value: {value} }}"),
+            Self::SetLineWidth { width } => write!(f, "SetLineWidth {{ width: {width} }}"),
+            Self::SetProgramPointSize { enabled } => write!(f, "SetProgramPointSize {{ enabled: {enabled} }}"),
+            Self::SetAlphaToCoverage { enabled } => write!(f, "SetAlphaToCoverage {{ enabled: {enabled} }}"),
+            Self::BeginPickPass { dimensions } => write!(f, "BeginPickPass {{ {}x{} }}", dimensions.0, dimensions.1),
+            Self::ReadPickPixel { x, y } => write!(f, "ReadPickPixel {{ x: {x}, y: {y} }}"),
+            Self::EndPickPass => write!(f, "EndPickPass"),
+            Self::CreateFramebuffer { id, width, height, color_attachment, depth_attachment } => write!(f, "CreateFramebuffer {{ id: {id}, {width}x{height}, color_attachment: {color_attachment:?}, depth_attachment: {depth_attachment} }}"),
+            Self::BindFramebuffer { id } => write!(f, "BindFramebuffer {{ id: {id} }}"),
+            Self::UnbindFramebuffer => write!(f, "UnbindFramebuffer"),
+            Self::ClearFramebuffer { colour } => write!(f, "ClearFramebuffer {{ colour: {colour} }}"),
+            Self::DeleteFramebuffer { id } => write!(f, "DeleteFramebuffer {{ id: {id} }}"),
         }
     }
 }
@@ -359,3 +1210,29 @@ pub struct VertexSource {
     vbo: glow::NativeBuffer,
     ebo: glow::NativeBuffer,
 }
+
+// the filter and wrap params of the texture bound to the active unit
+unsafe fn set_texture_options(glow: &glow::Context, options: TextureCreateOptions) {
+    let [mag, min, wrap_s, wrap_t] = options.gl_values();
+    glow.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MAG_FILTER, mag as i32);
+    glow.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MIN_FILTER, min as i32);
+    glow.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_WRAP_S, wrap_s as i32);
+    glow.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_WRAP_T, wrap_t as i32);
+}
+
+unsafe fn set_blend_func(glow: &glow::Context, alpha_mode: AlphaMode) {
+    match alpha_mode {
+        AlphaMode::Straight => glow.blend_func_separate(
+            glow::SRC_ALPHA,
+            glow::ONE_MINUS_SRC_ALPHA,
+            glow::ONE,
+            glow::ONE_MINUS_SRC_ALPHA,
+        ),
+        AlphaMode::Premultiplied => glow.blend_func_separate(
+            glow::ONE,
+            glow::ONE_MINUS_SRC_ALPHA,
+            glow::ONE,
+            glow::ONE_MINUS_SRC_ALPHA,
+        ),
+    }
+}
@@ -0,0 +1,134 @@
+use std::path::{Path, PathBuf};
+
+use crate::resolve_asset;
+
+// gl has no includes, so shader files are expanded here before they're compiled. an include is
+// looked up beside the file including it first and then from the asset root.
+pub(crate) struct ShaderSource {
+    pub source: String,
+    // every file the source was built from, the shader itself first. compile errors number their
+    // lines by source string, which is the index into this.
+    pub files: Vec<String>,
+}
+
+pub(crate) fn load_shader_source(filename: &str) -> Result<ShaderSource, String> {
+    let mut expanded = ShaderSource {
+        source: String::new(),
+        files: Vec::new(),
+    };
+    expand_file(filename, &mut Vec::new(), &mut expanded)?;
+
+    if expanded.files.len() > 1 {
+        let numbers = expanded
+            .files
+            .iter()
+            .enumerate()
+            .map(|(i, x)| format!("{i} = {x}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        log::debug!("shader '{filename}' source strings: {numbers}");
+    }
+    Ok(expanded)
+}
+
+// stack holds the files being expanded, for finding include cycles
+fn expand_file(
+    filename: &str,
+    stack: &mut Vec<(PathBuf, String)>,
+    expanded: &mut ShaderSource,
+) -> Result<(), String> {
+    let path = resolve_asset(filename);
+    let text = std::fs::read_to_string(&path)
+        .map_err(|e| format!("couldn't read file '{filename}' to string: {e}"))?;
+
+    let source_string = expanded.files.len();
+    expanded.files.push(filename.to_owned());
+    stack.push((
+        std::fs::canonicalize(&path).unwrap_or(path),
+        filename.to_owned(),
+    ));
+
+    for (i, line) in text.lines().enumerate() {
+        let line_number = i + 1;
+        let include = match parse_include(line) {
+            Some(x) => x.map_err(|e| format!("{filename}:{line_number}: {e}"))?,
+            None => {
+                expanded.source += line;
+                expanded.source += "\n";
+                continue;
+            }
+        };
+
+        let include_filename = find_include(filename, include).ok_or(format!(
+            "{filename}:{line_number}: couldn't find include '{include}'"
+        ))?;
+
+        let include_path = resolve_asset(&include_filename);
+        let include_path = std::fs::canonicalize(&include_path).unwrap_or(include_path);
+        if let Some(start) = stack.iter().position(|x| x.0 == include_path) {
+            let cycle = stack[start..]
+                .iter()
+                .map(|x| x.1.as_str())
+                .chain([include_filename.as_str()])
+                .collect::<Vec<_>>()
+                .join(" -> ");
+            return Err(format!("{filename}:{line_number}: include cycle: {cycle}"));
+        }
+
+        // #line keeps compile errors pointing at the file and line they came from
+        expanded.source += &format!("#line 1 {}\n", expanded.files.len());
+        expand_file(&include_filename, stack, expanded)?;
+        expanded.source += &format!("#line {} {source_string}\n", line_number + 1);
+    }
+
+    stack.pop();
+    Ok(())
+}
+
+// None for a line that isn't an include, otherwise the file named by `#include "file"`
+fn parse_include(line: &str) -> Option<Result<&str, String>> {
+    let directive = line.trim_start().strip_prefix('#')?.trim_start();
+    let rest = directive.strip_prefix("include")?;
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) && !rest.starts_with('"') {
+        return None; // some other directive starting with "include"
+    }
+
+    let rest = rest.trim();
+    let name = rest
+        .strip_prefix('"')
+        .and_then(|x| x.strip_suffix('"'))
+        .filter(|x| !x.is_empty() && !x.contains('"'));
+    Some(name.ok_or(format!("expected #include \"file\", got '{}'", line.trim())))
+}
+
+fn find_include(including: &str, include: &str) -> Option<String> {
+    let beside = Path::new(including).parent().map(|x| x.join(include));
+    beside
+        .into_iter()
+        .chain([PathBuf::from(include)])
+        .find(|x| resolve_asset(x).is_file())
+        .map(|x| x.to_string_lossy().into_owned())
+}
+
+// adds a #define for each (name, value) after the #version line, which has to stay first. a #line
+// after them keeps compile errors numbering lines as they are in the file.
+pub(crate) fn inject_defines(source: &str, defines: &[(String, String)]) -> String {
+    if defines.is_empty() {
+        return source.to_owned();
+    }
+
+    let mut lines: Vec<&str> = source.lines().collect();
+    let version_line = lines
+        .iter()
+        .position(|x| x.trim_start().starts_with("#version"));
+    let insert_at = version_line.map(|x| x + 1).unwrap_or(0);
+
+    let mut injected: Vec<String> = defines
+        .iter()
+        .map(|(name, value)| format!("#define {name} {value}"))
+        .collect();
+    injected.push(format!("#line {}", insert_at + 1));
+    lines.splice(insert_at..insert_at, injected.iter().map(String::as_str));
+
+    lines.join("\n") + "\n"
+}
@@ -1,16 +1,28 @@
 use std::{
-    collections::{BTreeMap, BTreeSet},
-    path::PathBuf,
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
 };
 
+use k9_proc_macros::console_command_internal;
 use uuid::Uuid;
 
 use crate::{
+    asset::resolve_asset,
+    camera::ScreenCamera,
+    entity_component::{ChildrenComponent, Entity, EntityTable, TransformComponent},
+    shaders,
     system::{FirstCallState, FrameState},
     System, SystemCallbacks,
 };
 
-use super::{component::GraphicsComponent, renderer::RenderCommand, Vertex};
+use super::{
+    component::{GraphicsComponent, GraphicsComponentImpl, GridGizmo, PickPass},
+    renderer::RenderCommand,
+    shader_include::{inject_defines, load_shader_source},
+    watcher::AssetWatcher,
+    Vertex,
+};
 
 pub enum GraphicsCommand {
     CreateVertexSource {
@@ -27,11 +39,21 @@ pub enum GraphicsCommand {
     CreateTextureRGB8 {
         id: Uuid,
         filepath: PathBuf,
+        options: TextureCreateOptions,
+    },
+    CreateTextureRGBA8 {
+        id: Uuid,
+        filepath: PathBuf,
+        alpha_mode: AlphaMode,
+        options: TextureCreateOptions,
     },
     BindTexture {
         id: Uuid,
         texture_slot: u8,
     },
+    UnbindTexture {
+        texture_slot: u8,
+    },
     DeleteTexture {
         id: Uuid,
     },
@@ -39,6 +61,7 @@ pub enum GraphicsCommand {
         id: Uuid,
         sh_type: ShaderType,
         filename: String,
+        defines: Vec<(String, String)>,
     },
     CreateShaderBuiltIn {
         id: Uuid,
@@ -60,6 +83,9 @@ pub enum GraphicsCommand {
     DrawElements {
         count: u32,
     },
+    DrawLines {
+        count: u32,
+    },
     CreateUniformLink {
         new_uniform_id: Uuid,
         existing_program_id: Uuid,
@@ -69,14 +95,119 @@ pub enum GraphicsCommand {
         id: Uuid,
         data: glam::Mat4,
     },
+    UploadUniformVec4 {
+        id: Uuid,
+        data: glam::Vec4,
+    },
+    UploadUniformVec2 {
+        id: Uuid,
+        data: glam::Vec2,
+    },
+    UploadUniformVec3 {
+        id: Uuid,
+        data: glam::Vec3,
+    },
+    UploadUniformF32 {
+        id: Uuid,
+        data: f32,
+    },
+    UploadUniformI32 {
+        id: Uuid,
+        data: i32,
+    },
+    BlitFullscreen {
+        texture_id: Uuid,
+        program_id: Uuid,
+    },
+    SetStencilTest {
+        enabled: bool,
+    },
+    StencilFunc {
+        func: StencilFunction,
+        reference: i32,
+        mask: u32,
+    },
+    StencilOp {
+        stencil_fail: StencilOperation,
+        depth_fail: StencilOperation,
+        pass: StencilOperation,
+    },
+    ClearStencil {
+        value: i32,
+    },
+    SetLineWidth {
+        width: f32,
+    },
+    SetProgramPointSize {
+        enabled: bool,
+    },
+    SetAlphaToCoverage {
+        enabled: bool,
+    },
+    BeginPickPass {
+        dimensions: (u32, u32),
+    },
+    ReadPickPixel {
+        x: i32,
+        y: i32,
+    },
+    EndPickPass,
+    CreateFramebuffer {
+        id: Uuid,
+        width: u32,
+        height: u32,
+        // the id the colour texture is bound by, None for no colour attachment
+        color_attachment: Option<Uuid>,
+        depth_attachment: bool,
+    },
+    BindFramebuffer {
+        id: Uuid,
+    },
+    UnbindFramebuffer,
+    ClearFramebuffer {
+        colour: glam::Vec4,
+    },
+    DeleteFramebuffer {
+        id: Uuid,
+    },
 }
 
 pub struct GraphicsSystem {
     tracked: BTreeSet<Uuid>,
     graphics_commands: Vec<GraphicsCommand>,
+    // reused between frames so the per frame command lists don't reallocate
+    graphics_commands_scratch: Vec<GraphicsCommand>,
+    interface_commands_scratch: Vec<GraphicsCommand>,
     texture_store: TextureStore,
     shader_store: ShaderStore,
     shader_program_store: ShaderProgramStore,
+    fullscreen_quad: Option<Uuid>,
+    // filled by the preload console command, drained on the next update
+    preload_requests: Arc<Mutex<Vec<PreloadRequest>>>,
+    // debug grid toggled by the show_grid console command, created the first time it's shown
+    show_grid: Arc<Mutex<bool>>,
+    debug_grid: GridGizmo,
+    debug_grid_created: bool,
+    // flat colour program the pick pass draws with, created on the first pick
+    pick_program: Option<PickProgram>,
+    // entities drawn by each pick pass still waiting on the renderer, index 0 of a pass is pick id 1
+    pick_passes: VecDeque<Vec<Uuid>>,
+    // reloads textures and shaders when their files change, see watch_assets
+    asset_watcher: Option<AssetWatcher>,
+    // framebuffer to the colour texture it owns, released from the texture store with it
+    framebuffer_textures: BTreeMap<Uuid, Uuid>,
+}
+
+enum PreloadRequest {
+    Texture(PathBuf, TextureFormat),
+    // vertex then fragment shader file
+    ShaderProgram(String, String),
+}
+
+struct PickProgram {
+    program: Uuid,
+    u_transform: Uuid,
+    u_colour: Uuid,
 }
 
 type RealId = Uuid;
@@ -84,29 +215,179 @@ type RefId = Uuid;
 struct TextureStore {
     ref_counts: BTreeMap<RealId, u32>,
     ref_real_map: BTreeMap<RefId, RealId>,
-    path_real_map: BTreeMap<PathBuf, RealId>,
+    path_real_map: BTreeMap<TextureKey, RealId>,
+    // stand in for textures that failed to load, created on the first failure. the store keeps a
+    // ref of its own so it's never deleted.
+    missing_texture: Option<RealId>,
 }
+// the same file can be loaded in different formats, each gets its own texture
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum TextureFormat {
+    Rgb8,
+    Rgba8(AlphaMode),
+}
+type TextureKey = (PathBuf, TextureFormat, TextureCreateOptions);
 impl TextureStore {
     pub fn new() -> Self {
         Self {
             ref_counts: BTreeMap::new(),
             ref_real_map: BTreeMap::new(),
             path_real_map: BTreeMap::new(),
+            missing_texture: None,
         }
     }
+
+    // points id at the missing texture, failed loads aren't cached so a fixed file loads next time
+    fn ref_missing_texture(&mut self, id: RefId, rval: &mut Vec<RenderCommand>) {
+        let real_id = match self.missing_texture {
+            Some(x) => x,
+            None => {
+                let real_id = Uuid::new_v4();
+                let (pixels, dimensions) = missing_texture_pixels();
+                rval.push(RenderCommand::CreateTextureRGB8 {
+                    id: real_id,
+                    dimensions,
+                    pixels,
+                    options: TextureCreateOptions {
+                        mag_filter: egui::TextureFilter::Nearest,
+                        ..Default::default()
+                    },
+                });
+                self.ref_counts.insert(real_id, 1);
+                self.missing_texture = Some(real_id);
+                real_id
+            }
+        };
+
+        self.ref_real_map.insert(id, real_id);
+        *self.ref_counts.entry(real_id).or_insert(0) += 1;
+    }
+}
+
+// sets each TransformComponent's world transform from the local ones down the hierarchy
+fn propagate_transforms(ents: &mut EntityTable) {
+    let roots: BTreeSet<Uuid> = [
+        ents.get_by_component::<TransformComponent>(),
+        ents.get_by_component::<ChildrenComponent>(),
+    ]
+    .into_iter()
+    .flatten()
+    .flat_map(|x| x.into_keys())
+    .filter(|id| ents.parent_of(*id).is_none())
+    .collect();
+
+    let local = |ents: &EntityTable, id: Uuid| {
+        ents.get_by_uuid(&id)
+            .and_then(|x| x.get_component::<TransformComponent>())
+            .map(|x| x.local)
+            .unwrap_or(glam::Mat4::IDENTITY)
+    };
+    let mut worlds = BTreeMap::new();
+    for root in roots {
+        worlds.insert(root, local(ents, root));
+        // parents come before their children so their world transform is always known
+        ents.walk_hierarchy(root, |id, _| {
+            let parent = ents.parent_of(id).and_then(|x| worlds.get(&x).copied());
+            let world = parent.unwrap_or(glam::Mat4::IDENTITY) * local(ents, id);
+            worlds.insert(id, world);
+        });
+    }
+
+    for (id, world) in worlds {
+        let transform = ents
+            .get_by_uuid_mut(&id)
+            .and_then(|x| x.get_component_mut::<TransformComponent>());
+        if let Some(transform) = transform {
+            transform.set_world(world);
+        }
+    }
+}
+
+// pixels and dimensions of an image file in the given format
+fn load_texture_pixels(
+    path: &Path,
+    format: TextureFormat,
+) -> Result<(Vec<u8>, (i32, i32)), String> {
+    let image = image::open(resolve_asset(path)).map_err(|e| e.to_string())?;
+    let dimensions = (image.width() as i32, image.height() as i32);
+    match format {
+        TextureFormat::Rgb8 => Ok((image.into_rgb8().into_raw(), dimensions)),
+        TextureFormat::Rgba8(alpha_mode) => {
+            let mut pixels = image.into_rgba8().into_raw();
+            // image files store straight alpha, so premultiplied textures are converted here
+            if alpha_mode == AlphaMode::Premultiplied {
+                for px in pixels.chunks_exact_mut(4) {
+                    let a = px[3] as u16;
+                    for c in &mut px[..3] {
+                        *c = ((*c as u16 * a + 127) / 255) as u8;
+                    }
+                }
+            }
+            Ok((pixels, dimensions))
+        }
+    }
+}
+
+fn create_texture_command(
+    id: RealId,
+    format: TextureFormat,
+    options: TextureCreateOptions,
+    pixels: Vec<u8>,
+    dimensions: (i32, i32),
+) -> RenderCommand {
+    match format {
+        TextureFormat::Rgb8 => RenderCommand::CreateTextureRGB8 {
+            id,
+            dimensions,
+            pixels,
+            options,
+        },
+        TextureFormat::Rgba8(alpha_mode) => RenderCommand::CreateTextureRGBA8 {
+            id,
+            dimensions,
+            pixels,
+            alpha_mode,
+            options,
+        },
+    }
+}
+
+// magenta and black checkerboard, loud enough that a broken asset can't be missed
+fn missing_texture_pixels() -> (Vec<u8>, (i32, i32)) {
+    const SIZE: usize = 64;
+    const CELL: usize = 8;
+    let mut pixels = Vec::with_capacity(SIZE * SIZE * 3);
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            if (x / CELL + y / CELL) % 2 == 0 {
+                pixels.extend_from_slice(&[255, 0, 255]);
+            } else {
+                pixels.extend_from_slice(&[0, 0, 0]);
+            }
+        }
+    }
+    (pixels, (SIZE as i32, SIZE as i32))
 }
 
 struct ShaderStore {
     ref_counts: BTreeMap<RealId, u32>,
     ref_real_map: BTreeMap<RefId, RealId>,
-    name_real_map: BTreeMap<String, RealId>,
+    name_real_map: BTreeMap<ShaderKey, RealId>,
+    // shaders loaded from files rather than built in, the ones asset watching can reload
+    file_types: BTreeMap<ShaderKey, ShaderType>,
+    // files pulled in by each file shader's #includes, a change to one reloads the shader too
+    file_includes: BTreeMap<ShaderKey, Vec<String>>,
 }
+// a file compiled with different defines is a different shader, built in shaders have none
+type ShaderKey = (String, Vec<(String, String)>);
 impl ShaderStore {
     pub fn new() -> Self {
         Self {
             ref_counts: BTreeMap::new(),
             ref_real_map: BTreeMap::new(),
             name_real_map: BTreeMap::new(),
+            file_types: BTreeMap::new(),
+            file_includes: BTreeMap::new(),
         }
     }
 }
@@ -130,19 +411,314 @@ impl GraphicsSystem {
     pub fn new() -> Self {
         Self {
             graphics_commands: Vec::new(),
+            graphics_commands_scratch: Vec::new(),
+            interface_commands_scratch: Vec::new(),
             tracked: BTreeSet::new(),
             texture_store: TextureStore::new(),
             shader_store: ShaderStore::new(),
             shader_program_store: ShaderProgramStore::new(),
+            fullscreen_quad: None,
+            preload_requests: Arc::new(Mutex::new(Vec::new())),
+            asset_watcher: None,
+            framebuffer_textures: BTreeMap::new(),
+            show_grid: Arc::new(Mutex::new(false)),
+            debug_grid: GridGizmo::default(),
+            debug_grid_created: false,
+            pick_program: None,
+            pick_passes: VecDeque::new(),
         }
     }
 
-    pub fn get_render_commands(&mut self) -> Vec<RenderCommand> {
-        let mut rval = Vec::new();
+    // matches pick ids read back by the renderer against the passes that drew them, returns the
+    // result of the latest pass if any finished. Some(None) means nothing was under the pixel.
+    pub fn resolve_picks(&mut self, results: Vec<u32>) -> Option<Option<Uuid>> {
+        let mut rval = None;
+        for pick_id in results {
+            let pass = match self.pick_passes.pop_front() {
+                Some(x) => x,
+                None => {
+                    log::error!("got pick result {pick_id} with no pick pass waiting on it");
+                    continue;
+                }
+            };
+            rval = Some(match pick_id {
+                0 => None,
+                n => pass.get(n as usize - 1).copied(),
+            });
+        }
+        rval
+    }
+
+    // draws every graphics entity in a unique flat colour and reads back the one under position
+    fn render_pick_pass(
+        &mut self,
+        k9cmd: &mut GraphicsCommandInterface,
+        screen_camera: &ScreenCamera,
+        (width, height): (u32, u32),
+        gfx_ents: &mut BTreeMap<Uuid, &mut Entity>,
+        position: (i32, i32),
+    ) {
+        let (u_transform, u_colour, program) = {
+            let pick_program = self.get_pick_program(k9cmd);
+            (
+                pick_program.u_transform,
+                pick_program.u_colour,
+                pick_program.program,
+            )
+        };
+        let pick = PickPass { u_transform };
+
+        k9cmd.cmds.push(GraphicsCommand::BeginPickPass {
+            dimensions: (width, height),
+        });
+        k9cmd.use_shader_program(program);
+
+        let mut drawn = Vec::new();
+        for (id, gfx_ent) in gfx_ents {
+            if let Some(gfx_comp) = gfx_ent.get_component_mut::<GraphicsComponent>() {
+                // pick ids are 24 bit rgb, 0 is left for the cleared background
+                let pick_id = drawn.len() as u32 + 1;
+                if pick_id > 0xFF_FFFF {
+                    log::warn!("too many graphics entities to pick from, the rest are skipped");
+                    break;
+                }
+                let colour = glam::Vec4::new(
+                    (pick_id & 0xFF) as f32 / 255.0,
+                    ((pick_id >> 8) & 0xFF) as f32 / 255.0,
+                    ((pick_id >> 16) & 0xFF) as f32 / 255.0,
+                    1.0,
+                );
+                k9cmd.upload_uniform_vec4(u_colour, colour);
+                gfx_comp.render_pick(k9cmd, screen_camera, &pick);
+                drawn.push(*id);
+            }
+        }
+
+        // window coordinates start at the top left, gl's at the bottom left
+        k9cmd.cmds.push(GraphicsCommand::ReadPickPixel {
+            x: position.0,
+            y: height as i32 - 1 - position.1,
+        });
+        k9cmd.cmds.push(GraphicsCommand::EndPickPass);
+        self.pick_passes.push_back(drawn);
+    }
 
-        let mut gfx_commands = Vec::new();
-        gfx_commands.append(&mut self.graphics_commands);
-        for cmd in gfx_commands {
+    fn get_pick_program(&mut self, k9cmd: &mut GraphicsCommandInterface) -> &PickProgram {
+        self.pick_program.get_or_insert_with(|| {
+            let sh_vert = k9cmd.create_shader_builtin(BuiltInShader::LineVert);
+            let sh_frag = k9cmd.create_shader_builtin(BuiltInShader::LineFrag);
+            let program = k9cmd.create_shader_program([sh_vert, sh_frag].to_vec());
+            PickProgram {
+                program,
+                u_transform: k9cmd.create_uniform_link(program, shaders::LINE_TRANSFORM),
+                u_colour: k9cmd.create_uniform_link(program, shaders::LINE_COLOUR),
+            }
+        })
+    }
+
+    // creates the shared ndc quad used by blits the first time it's requested
+    fn get_fullscreen_quad(&mut self, rcmds: &mut Vec<RenderCommand>) -> Uuid {
+        if let Some(id) = self.fullscreen_quad {
+            return id;
+        }
+
+        let id = Uuid::new_v4();
+        let vertices = [
+            Vertex {
+                x: -1.0,
+                y: 1.0,
+                z: 0.0,
+                u: 0.0,
+                v: 1.0,
+            }, // tl
+            Vertex {
+                x: 1.0,
+                y: 1.0,
+                z: 0.0,
+                u: 1.0,
+                v: 1.0,
+            }, // tr
+            Vertex {
+                x: 1.0,
+                y: -1.0,
+                z: 0.0,
+                u: 1.0,
+                v: 0.0,
+            }, // br
+            Vertex {
+                x: -1.0,
+                y: -1.0,
+                z: 0.0,
+                u: 0.0,
+                v: 0.0,
+            }, // bl
+        ]
+        .to_vec();
+        let indices = [0, 1, 2, 0, 2, 3].to_vec();
+
+        rcmds.push(RenderCommand::CreateVertexSource {
+            id,
+            vertices,
+            indices,
+        });
+        self.fullscreen_quad = Some(id);
+        id
+    }
+
+    // reload file backed textures and shaders when their files change on disk
+    pub fn watch_assets(&mut self) {
+        match AssetWatcher::new() {
+            Ok(x) => self.asset_watcher = Some(x),
+            Err(e) => log::error!("couldn't start watching assets: {e}"),
+        }
+    }
+
+    // changed assets are reloaded under their existing ids, so nothing holding them notices. a
+    // file that fails to load or compile keeps the old version.
+    fn reload_changed_assets(&mut self, rval: &mut Vec<RenderCommand>) {
+        let watcher = match &mut self.asset_watcher {
+            Some(x) => x,
+            None => return,
+        };
+        let texture_paths = self
+            .texture_store
+            .path_real_map
+            .keys()
+            .map(|x| x.0.as_path());
+        let shader_paths = self.shader_store.file_types.keys().map(|x| Path::new(&x.0));
+        let include_paths = self
+            .shader_store
+            .file_includes
+            .values()
+            .flatten()
+            .map(Path::new);
+        // shaders can share includes, each file is only passed once
+        let paths: BTreeSet<&Path> = texture_paths
+            .chain(shader_paths)
+            .chain(include_paths)
+            .collect();
+        let changed = watcher.poll(paths.into_iter());
+
+        let mut changed_shaders = BTreeSet::new();
+        for path in changed {
+            for ((key_path, format, options), real_id) in &self.texture_store.path_real_map {
+                if *key_path != path {
+                    continue;
+                }
+                match load_texture_pixels(&path, *format) {
+                    Ok((pixels, dimensions)) => {
+                        log::info!("reloading texture {path:?}");
+                        rval.push(RenderCommand::DeleteTexture { id: *real_id });
+                        rval.push(create_texture_command(
+                            *real_id, *format, *options, pixels, dimensions,
+                        ));
+                    }
+                    Err(e) => log::error!("couldn't reload image {path:?}: {e}"),
+                }
+            }
+
+            let name = path.to_string_lossy();
+            for (key, includes) in &self.shader_store.file_includes {
+                if key.0 == name || includes.iter().any(|x| *x == name) {
+                    changed_shaders.insert(key.clone());
+                }
+            }
+        }
+
+        // every define variant of a changed file is reloaded
+        for key in changed_shaders {
+            let sh_type = self.shader_store.file_types.get(&key);
+            let real_id = self.shader_store.name_real_map.get(&key);
+            if let (Some(sh_type), Some(real_id)) = (sh_type, real_id) {
+                let name = &key.0;
+                match load_shader_source(name) {
+                    Ok(mut x) => {
+                        log::info!("reloading shader '{name}'");
+                        rval.push(RenderCommand::ReloadShader {
+                            id: *real_id,
+                            sh_type: *sh_type,
+                            source: inject_defines(&x.source, &key.1),
+                        });
+                        // the includes may have changed with the file
+                        x.files.remove(0);
+                        self.shader_store.file_includes.insert(key, x.files);
+                    }
+                    Err(e) => log::error!("couldn't reload shader '{name}': {e}"),
+                }
+            }
+        }
+    }
+
+    // shares an already loaded texture when the key matches, otherwise loads it
+    fn create_texture(&mut self, id: RefId, key: TextureKey, rval: &mut Vec<RenderCommand>) {
+        let store = &mut self.texture_store;
+        if let Some(real_id) = store.path_real_map.get(&key) {
+            store.ref_real_map.insert(id, *real_id);
+            if let Some(rc) = store.ref_counts.get_mut(real_id) {
+                *rc += 1;
+            } else {
+                log::error!("texture store corrupted on create");
+            }
+            return;
+        }
+
+        let (path, format, options) = &key;
+        let (pixels, dimensions) = match load_texture_pixels(path, *format) {
+            Ok(x) => x,
+            Err(e) => {
+                log::error!("couldn't open image {path:?}, using the missing texture: {e}");
+                store.ref_missing_texture(id, rval);
+                return;
+            }
+        };
+        rval.push(create_texture_command(
+            id, *format, *options, pixels, dimensions,
+        ));
+
+        store.path_real_map.insert(key, id);
+        store.ref_real_map.insert(id, id);
+        store.ref_counts.insert(id, 1);
+    }
+
+    // creates the components of entities new since the last update and deletes those of entities
+    // deleted since then
+    fn track_entities(
+        &mut self,
+        ents: &mut EntityTable,
+        k9cmd: &mut GraphicsCommandInterface,
+        screen_camera: &ScreenCamera,
+    ) {
+        if let Some(delete_ents) = ents.get_by_component_delete_mut::<GraphicsComponent>() {
+            for (d_id, d_ent) in delete_ents {
+                // delete only pairs with a create, an entity added and deleted before this update
+                // never had its component created so there's nothing to free
+                if !self.tracked.remove(&d_id) {
+                    continue;
+                }
+                if let Some(gfx_comp) = d_ent.get_component_mut::<GraphicsComponent>() {
+                    gfx_comp.delete(k9cmd, screen_camera);
+                }
+            }
+        }
+
+        if let Some(gfx_ents) = ents.get_by_component_mut::<GraphicsComponent>() {
+            for (n_id, n_ent) in gfx_ents {
+                if self.tracked.insert(n_id) {
+                    if let Some(gfx_comp) = n_ent.get_component_mut::<GraphicsComponent>() {
+                        gfx_comp.create(k9cmd, screen_camera);
+                    }
+                }
+            }
+        }
+    }
+
+    // appends this frame's render commands to rval, rval is expected to be empty
+    pub fn get_render_commands(&mut self, rval: &mut Vec<RenderCommand>) {
+        self.reload_changed_assets(rval);
+
+        let mut gfx_commands = std::mem::take(&mut self.graphics_commands_scratch);
+        std::mem::swap(&mut gfx_commands, &mut self.graphics_commands);
+        for cmd in gfx_commands.drain(..) {
             match cmd {
                 GraphicsCommand::CreateVertexSource {
                     id,
@@ -161,37 +737,24 @@ impl GraphicsSystem {
                 GraphicsCommand::BindVertexSource { id } => {
                     rval.push(RenderCommand::BindVertexSource { id })
                 }
-                GraphicsCommand::CreateTextureRGB8 { id, filepath } => {
-                    if let Some(real_id) = self.texture_store.path_real_map.get(&filepath) {
-                        self.texture_store.ref_real_map.insert(id, *real_id);
-                        if let Some(rc) = self.texture_store.ref_counts.get_mut(real_id) {
-                            *rc += 1;
-                        } else {
-                            log::error!("texture store corrupted on create rgb8");
-                            continue;
-                        }
-                    } else {
-                        let (pixels, dimensions) = match image::open(&filepath) {
-                            Ok(x) => {
-                                let dimensions = (x.width() as i32, x.height() as i32);
-                                (x.into_rgb8().into_raw(), dimensions)
-                            }
-                            Err(e) => {
-                                log::error!("couldn't open image {filepath:?}: {e}");
-                                continue;
-                            }
-                        };
-
-                        rval.push(RenderCommand::CreateTextureRGB8 {
-                            id,
-                            dimensions,
-                            pixels,
-                        });
-
-                        self.texture_store.path_real_map.insert(filepath, id);
-                        self.texture_store.ref_real_map.insert(id, id);
-                        self.texture_store.ref_counts.insert(id, 1);
-                    }
+                GraphicsCommand::CreateTextureRGB8 {
+                    id,
+                    filepath,
+                    options,
+                } => {
+                    self.create_texture(id, (filepath, TextureFormat::Rgb8, options), rval);
+                }
+                GraphicsCommand::CreateTextureRGBA8 {
+                    id,
+                    filepath,
+                    alpha_mode,
+                    options,
+                } => {
+                    let key = (filepath, TextureFormat::Rgba8(alpha_mode), options);
+                    self.create_texture(id, key, rval);
+                }
+                GraphicsCommand::UnbindTexture { texture_slot } => {
+                    rval.push(RenderCommand::UnbindTexture { texture_slot });
                 }
                 GraphicsCommand::BindTexture { id, texture_slot } => {
                     if let Some(real_id) = self.texture_store.ref_real_map.get(&id) {
@@ -234,8 +797,10 @@ impl GraphicsSystem {
                     id,
                     sh_type,
                     filename,
+                    defines,
                 } => {
-                    if let Some(real_id) = self.shader_store.name_real_map.get(&filename) {
+                    let key = (filename, defines);
+                    if let Some(real_id) = self.shader_store.name_real_map.get(&key) {
                         if let Some(ref_count) = self.shader_store.ref_counts.get_mut(&real_id) {
                             *ref_count += 1;
                         } else {
@@ -243,20 +808,25 @@ impl GraphicsSystem {
                         }
                         self.shader_store.ref_real_map.insert(id, *real_id);
                     } else {
-                        let source = match std::fs::read_to_string(&filename) {
+                        let mut source = match load_shader_source(&key.0) {
                             Ok(x) => x,
                             Err(e) => {
-                                log::error!("couldn't read file '{filename}' to string: {e}");
+                                log::error!("couldn't load shader '{}': {e}", key.0);
                                 continue;
                             }
                         };
                         rval.push(RenderCommand::CreateShader {
                             id,
                             sh_type,
-                            source,
+                            source: inject_defines(&source.source, &key.1),
                         });
 
-                        self.shader_store.name_real_map.insert(filename, id);
+                        self.shader_store.file_types.insert(key.clone(), sh_type);
+                        source.files.remove(0);
+                        self.shader_store
+                            .file_includes
+                            .insert(key.clone(), source.files);
+                        self.shader_store.name_real_map.insert(key, id);
                         self.shader_store.ref_counts.insert(id, 1);
                         self.shader_store.ref_real_map.insert(id, id);
                     }
@@ -275,7 +845,8 @@ impl GraphicsSystem {
                          source: &'static str,
                          id: Uuid,
                          rcmds: &mut Vec<RenderCommand>| {
-                        if let Some(real_id) = this.shader_store.name_real_map.get(name) {
+                        let key = (name.to_string(), Vec::new());
+                        if let Some(real_id) = this.shader_store.name_real_map.get(&key) {
                             if let Some(ref_count) = this.shader_store.ref_counts.get_mut(real_id) {
                                 *ref_count += 1;
                             } else {
@@ -289,7 +860,7 @@ impl GraphicsSystem {
                                 source: source.to_string(),
                             });
 
-                            this.shader_store.name_real_map.insert(name.to_string(), id);
+                            this.shader_store.name_real_map.insert(key, id);
                             this.shader_store.ref_counts.insert(id, 1);
                             this.shader_store.ref_real_map.insert(id, id);
                         }
@@ -297,27 +868,53 @@ impl GraphicsSystem {
 
                     match shader {
                         BuiltInShader::TexQuadFrag => {
-                            const SOURCE: &'static str =
-                                include_str!("../../assets/shaders/k9_texquad.frag.glsl");
                             CORE_FUNC(
                                 self,
                                 ShaderType::Fragment,
                                 "k9_built_in_texquad.frag.glsl",
-                                SOURCE,
+                                shaders::TEXQUAD_FRAG,
                                 id,
-                                &mut rval,
+                                rval,
                             );
                         }
                         BuiltInShader::TexQuadVert => {
-                            const SOURCE: &'static str =
-                                include_str!("../../assets/shaders/k9_texquad.vert.glsl");
                             CORE_FUNC(
                                 self,
                                 ShaderType::Vertex,
                                 "k9_built_in_texquad.vert.glsl",
-                                SOURCE,
+                                shaders::TEXQUAD_VERT,
+                                id,
+                                rval,
+                            );
+                        }
+                        BuiltInShader::TexQuadCutoutFrag => {
+                            CORE_FUNC(
+                                self,
+                                ShaderType::Fragment,
+                                "k9_built_in_texquad_cutout.frag.glsl",
+                                shaders::TEXQUAD_CUTOUT_FRAG,
+                                id,
+                                rval,
+                            );
+                        }
+                        BuiltInShader::LineFrag => {
+                            CORE_FUNC(
+                                self,
+                                ShaderType::Fragment,
+                                "k9_built_in_line.frag.glsl",
+                                shaders::LINE_FRAG,
+                                id,
+                                rval,
+                            );
+                        }
+                        BuiltInShader::LineVert => {
+                            CORE_FUNC(
+                                self,
+                                ShaderType::Vertex,
+                                "k9_built_in_line.vert.glsl",
+                                shaders::LINE_VERT,
                                 id,
-                                &mut rval,
+                                rval,
                             );
                         }
                     }
@@ -345,6 +942,13 @@ impl GraphicsSystem {
                                 .name_real_map
                                 .drain_filter(|_k, v| *v != real_id)
                                 .collect();
+                            let names = &self.shader_store.name_real_map;
+                            self.shader_store
+                                .file_types
+                                .retain(|k, _| names.contains_key(k));
+                            self.shader_store
+                                .file_includes
+                                .retain(|k, _| names.contains_key(k));
 
                             rval.push(RenderCommand::DeleteShader { id: real_id });
                         }
@@ -420,6 +1024,9 @@ impl GraphicsSystem {
                 GraphicsCommand::DrawElements { count } => {
                     rval.push(RenderCommand::DrawElements { count });
                 }
+                GraphicsCommand::DrawLines { count } => {
+                    rval.push(RenderCommand::DrawLines { count });
+                }
                 GraphicsCommand::CreateUniformLink {
                     new_uniform_id,
                     existing_program_id,
@@ -434,59 +1041,284 @@ impl GraphicsSystem {
                 GraphicsCommand::UploadUniformMat4 { id, data } => {
                     rval.push(RenderCommand::UploadUniformMat4 { id, data });
                 }
+                GraphicsCommand::UploadUniformVec4 { id, data } => {
+                    rval.push(RenderCommand::UploadUniformVec4 { id, data });
+                }
+                GraphicsCommand::UploadUniformVec2 { id, data } => {
+                    rval.push(RenderCommand::UploadUniformVec2 { id, data });
+                }
+                GraphicsCommand::UploadUniformVec3 { id, data } => {
+                    rval.push(RenderCommand::UploadUniformVec3 { id, data });
+                }
+                GraphicsCommand::UploadUniformF32 { id, data } => {
+                    rval.push(RenderCommand::UploadUniformF32 { id, data });
+                }
+                GraphicsCommand::UploadUniformI32 { id, data } => {
+                    rval.push(RenderCommand::UploadUniformI32 { id, data });
+                }
+                GraphicsCommand::BlitFullscreen {
+                    texture_id,
+                    program_id,
+                } => {
+                    let real_program_id =
+                        match self.shader_program_store.ref_real_map.get(&program_id) {
+                            Some(x) => *x,
+                            None => {
+                                log::error!("couldn't get shader program id to blit: {program_id}");
+                                continue;
+                            }
+                        };
+                    let real_texture_id = match self.texture_store.ref_real_map.get(&texture_id) {
+                        Some(x) => *x,
+                        None => {
+                            log::error!("couldn't get texture id to blit: {texture_id}");
+                            continue;
+                        }
+                    };
+
+                    let quad_id = self.get_fullscreen_quad(rval);
+                    rval.push(RenderCommand::UseShaderProgram {
+                        id: real_program_id,
+                    });
+                    rval.push(RenderCommand::BindVertexSource { id: quad_id });
+                    rval.push(RenderCommand::BindTexture {
+                        id: real_texture_id,
+                        texture_slot: 0,
+                    });
+                    rval.push(RenderCommand::DrawElements { count: 6 });
+                }
+                GraphicsCommand::SetStencilTest { enabled } => {
+                    rval.push(RenderCommand::SetStencilTest { enabled });
+                }
+                GraphicsCommand::StencilFunc {
+                    func,
+                    reference,
+                    mask,
+                } => {
+                    rval.push(RenderCommand::StencilFunc {
+                        func,
+                        reference,
+                        mask,
+                    });
+                }
+                GraphicsCommand::StencilOp {
+                    stencil_fail,
+                    depth_fail,
+                    pass,
+                } => {
+                    rval.push(RenderCommand::StencilOp {
+                        stencil_fail,
+                        depth_fail,
+                        pass,
+                    });
+                }
+                GraphicsCommand::ClearStencil { value } => {
+                    rval.push(RenderCommand::ClearStencil { value });
+                }
+                GraphicsCommand::SetLineWidth { width } => {
+                    rval.push(RenderCommand::SetLineWidth { width });
+                }
+                GraphicsCommand::SetProgramPointSize { enabled } => {
+                    rval.push(RenderCommand::SetProgramPointSize { enabled });
+                }
+                GraphicsCommand::SetAlphaToCoverage { enabled } => {
+                    rval.push(RenderCommand::SetAlphaToCoverage { enabled });
+                }
+                GraphicsCommand::BeginPickPass { dimensions } => {
+                    rval.push(RenderCommand::BeginPickPass { dimensions });
+                }
+                GraphicsCommand::ReadPickPixel { x, y } => {
+                    rval.push(RenderCommand::ReadPickPixel { x, y });
+                }
+                GraphicsCommand::EndPickPass => {
+                    rval.push(RenderCommand::EndPickPass);
+                }
+                GraphicsCommand::CreateFramebuffer {
+                    id,
+                    width,
+                    height,
+                    color_attachment,
+                    depth_attachment,
+                } => {
+                    // the colour texture is bound like any other texture, so it goes in the store
+                    if let Some(texture_id) = color_attachment {
+                        self.texture_store
+                            .ref_real_map
+                            .insert(texture_id, texture_id);
+                        self.texture_store.ref_counts.insert(texture_id, 1);
+                        self.framebuffer_textures.insert(id, texture_id);
+                    }
+                    rval.push(RenderCommand::CreateFramebuffer {
+                        id,
+                        width,
+                        height,
+                        color_attachment,
+                        depth_attachment,
+                    });
+                }
+                GraphicsCommand::BindFramebuffer { id } => {
+                    rval.push(RenderCommand::BindFramebuffer { id });
+                }
+                GraphicsCommand::ClearFramebuffer { colour } => {
+                    rval.push(RenderCommand::ClearFramebuffer { colour });
+                }
+                GraphicsCommand::UnbindFramebuffer => {
+                    rval.push(RenderCommand::UnbindFramebuffer);
+                }
+                GraphicsCommand::DeleteFramebuffer { id } => {
+                    // the renderer deletes the colour texture along with the framebuffer
+                    if let Some(texture_id) = self.framebuffer_textures.remove(&id) {
+                        self.texture_store.ref_real_map.remove(&texture_id);
+                        self.texture_store.ref_counts.remove(&texture_id);
+                    }
+                    rval.push(RenderCommand::DeleteFramebuffer { id });
+                }
             }
         }
 
-        rval
+        self.graphics_commands_scratch = gfx_commands;
     }
 }
 impl System for GraphicsSystem {
     const UUID: Uuid = uuid::uuid!("adcc866b-a2a4-4225-aa97-8aec4cc81107");
 }
 impl SystemCallbacks for GraphicsSystem {
-    fn first_call(&mut self, _first_call_state: FirstCallState, _state: FrameState) {}
+    fn first_call(&mut self, first_call_state: FirstCallState, _state: FrameState) {
+        let requests = self.preload_requests.clone();
+        let cc = console_command_internal!(
+            "loads a texture or shader program ahead of time so its first use doesn't stall.",
+            {
+                kind: one_of["texture", "shader_program"] "what to load",
+                paths: String[] "the texture file, or the vertex then fragment shader files",
+                opt format: one_of["rgb8", "rgba8"] "the texture's format, rgb8 by default"
+            },
+            |ccf, kind: String, paths: Vec<String>, format: Option<String>| {
+                let request = match (kind.as_str(), paths.as_slice()) {
+                    ("texture", [path]) => {
+                        let format = match format.as_deref() {
+                            Some("rgba8") => TextureFormat::Rgba8(AlphaMode::Straight),
+                            _ => TextureFormat::Rgb8,
+                        };
+                        PreloadRequest::Texture(PathBuf::from(path), format)
+                    }
+                    ("shader_program", [vert, frag]) => {
+                        PreloadRequest::ShaderProgram(vert.clone(), frag.clone())
+                    }
+                    ("texture", _) => return Err("a texture takes one path".to_owned()),
+                    _ => {
+                        return Err(
+                            "a shader program takes a vertex and a fragment shader".to_owned()
+                        )
+                    }
+                };
+                requests.lock().unwrap().push(request);
+                Ok(())
+            }
+        );
+        first_call_state
+            .console_commands
+            .entry("preload".to_owned())
+            .and_modify(|_| log::warn!("console command 'preload' was overwritten."))
+            .or_insert(cc);
+
+        let show_grid = self.show_grid.clone();
+        let cc = console_command_internal!(
+            "shows a ground grid on the xz plane with the x/y/z axes at the origin.",
+            { value: bool },
+            |ccf, value| {
+                *show_grid.lock().unwrap() = value;
+                Ok(())
+            }
+        );
+        first_call_state
+            .console_commands
+            .entry("show_grid".to_owned())
+            .and_modify(|_| log::warn!("console command 'show_grid' was overwritten."))
+            .or_insert(cc);
+    }
     fn update(&mut self, state: FrameState) {
         let ents = state.ents;
 
         // generate render commands
-        let mut k9cmd = GraphicsCommandInterface::new();
+        let mut k9cmd = GraphicsCommandInterface::from_raw(std::mem::take(
+            &mut self.interface_commands_scratch,
+        ));
 
-        // get delete entities
-        if let Some(delete_ents) = ents.get_by_component_delete_mut::<GraphicsComponent>() {
-            for (d_id, d_ent) in delete_ents {
-                if let Some(gfx_comp) = d_ent.get_component_mut::<GraphicsComponent>() {
-                    gfx_comp.delete(&mut k9cmd, &state.screen_camera);
+        for request in self.preload_requests.lock().unwrap().drain(..) {
+            match request {
+                PreloadRequest::Texture(path, format) => {
+                    log::info!("preloading texture {path:?}");
+                    match format {
+                        TextureFormat::Rgb8 => k9cmd.preload_texture_rgb8(path),
+                        TextureFormat::Rgba8(alpha_mode) => {
+                            k9cmd.preload_texture_rgba8(path, alpha_mode)
+                        }
+                    }
+                }
+                PreloadRequest::ShaderProgram(vert, frag) => {
+                    log::info!("preloading shader program '{vert}', '{frag}'");
+                    k9cmd.preload_shader_program(&[
+                        (ShaderType::Vertex, vert.as_str()),
+                        (ShaderType::Fragment, frag.as_str()),
+                    ]);
                 }
-                self.tracked.remove(&d_id);
             }
         }
 
-        // get add entities
-        if let Some(mut gfx_ents) = ents.get_by_component_mut::<GraphicsComponent>() {
-            for (n_id, n_ent) in &mut gfx_ents {
-                if !self.tracked.contains(n_id) {
-                    self.tracked.insert(*n_id);
-                    if let Some(gfx_comp) = n_ent.get_component_mut::<GraphicsComponent>() {
-                        gfx_comp.create(&mut k9cmd, &state.screen_camera);
-                    }
-                }
-            }
+        self.track_entities(ents, &mut k9cmd, &state.screen_camera);
+        propagate_transforms(ents);
 
+        if let Some(mut gfx_ents) = ents.get_by_component_mut::<GraphicsComponent>() {
             // call render on survivors
-            for (_, gfx_ent) in gfx_ents {
+            for (_, gfx_ent) in &mut gfx_ents {
+                let world = gfx_ent
+                    .get_component::<TransformComponent>()
+                    .map(|x| x.world());
                 if let Some(gfx_comp) = gfx_ent.get_component_mut::<GraphicsComponent>() {
+                    if let Some(world) = world {
+                        gfx_comp.set_world_transform(world);
+                    }
                     gfx_comp.render(&mut k9cmd, &state.screen_camera);
                 }
             }
+
+            if let Some(position) = state.requests.pick.take() {
+                self.render_pick_pass(
+                    &mut k9cmd,
+                    &state.screen_camera,
+                    state.screen_dimensions,
+                    &mut gfx_ents,
+                    position,
+                );
+            }
+        } else if let Some(position) = state.requests.pick.take() {
+            // nothing to draw, the pass still runs so the miss gets reported
+            self.render_pick_pass(
+                &mut k9cmd,
+                &state.screen_camera,
+                state.screen_dimensions,
+                &mut BTreeMap::new(),
+                position,
+            );
+        }
+
+        if *self.show_grid.lock().unwrap() {
+            if !self.debug_grid_created {
+                self.debug_grid_created = true;
+                self.debug_grid.create(&mut k9cmd, &state.screen_camera);
+            }
+            self.debug_grid.render(&mut k9cmd, &state.screen_camera);
         }
 
-        self.graphics_commands.append(&mut k9cmd.into_raw());
+        let mut cmds = k9cmd.into_raw();
+        self.graphics_commands.append(&mut cmds);
+        self.interface_commands_scratch = cmds;
     }
 
     fn exiting(&mut self, _state: FrameState) {}
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum ShaderType {
     Vertex,
     Fragment,
@@ -504,9 +1336,141 @@ impl Into<u32> for ShaderType {
     }
 }
 
+pub struct FramebufferIds {
+    pub framebuffer: Uuid,
+    // None when the framebuffer was made without a colour attachment
+    pub color_texture: Option<Uuid>,
+}
+
+// straight alpha textures are drawn with the standard src_alpha, 1 - src_alpha blend,
+// premultiplied ones with 1, 1 - src_alpha
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AlphaMode {
+    Straight,
+    Premultiplied,
+}
+
+// sampling state for a created texture, applied before its pixels are uploaded. defaults to
+// linear filtering and repeat wrapping.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextureCreateOptions {
+    pub mag_filter: egui::TextureFilter,
+    pub min_filter: egui::TextureFilter,
+    pub wrap_s: WrapMode,
+    pub wrap_t: WrapMode,
+}
+impl Default for TextureCreateOptions {
+    fn default() -> Self {
+        Self {
+            mag_filter: egui::TextureFilter::Linear,
+            min_filter: egui::TextureFilter::Linear,
+            wrap_s: WrapMode::Repeat,
+            wrap_t: WrapMode::Repeat,
+        }
+    }
+}
+// ordered by gl values so options can be part of the texture store's key, egui's TextureFilter
+// isn't Ord
+impl PartialOrd for TextureCreateOptions {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for TextureCreateOptions {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.gl_values().cmp(&other.gl_values())
+    }
+}
+impl TextureCreateOptions {
+    // mag filter, min filter, wrap s, wrap t
+    pub(crate) fn gl_values(&self) -> [u32; 4] {
+        let filter = |x: egui::TextureFilter| match x {
+            egui::TextureFilter::Linear => glow::LINEAR,
+            egui::TextureFilter::Nearest => glow::NEAREST,
+        };
+        [
+            filter(self.mag_filter),
+            filter(self.min_filter),
+            self.wrap_s.into(),
+            self.wrap_t.into(),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WrapMode {
+    Repeat,
+    ClampToEdge,
+    MirroredRepeat,
+}
+impl Into<u32> for WrapMode {
+    fn into(self) -> u32 {
+        match self {
+            Self::Repeat => glow::REPEAT,
+            Self::ClampToEdge => glow::CLAMP_TO_EDGE,
+            Self::MirroredRepeat => glow::MIRRORED_REPEAT,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum StencilFunction {
+    Never,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    Equal,
+    NotEqual,
+    Always,
+}
+impl Into<u32> for StencilFunction {
+    fn into(self) -> u32 {
+        match self {
+            Self::Never => glow::NEVER,
+            Self::Less => glow::LESS,
+            Self::LessEqual => glow::LEQUAL,
+            Self::Greater => glow::GREATER,
+            Self::GreaterEqual => glow::GEQUAL,
+            Self::Equal => glow::EQUAL,
+            Self::NotEqual => glow::NOTEQUAL,
+            Self::Always => glow::ALWAYS,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum StencilOperation {
+    Keep,
+    Zero,
+    Replace,
+    Increment,
+    IncrementWrap,
+    Decrement,
+    DecrementWrap,
+    Invert,
+}
+impl Into<u32> for StencilOperation {
+    fn into(self) -> u32 {
+        match self {
+            Self::Keep => glow::KEEP,
+            Self::Zero => glow::ZERO,
+            Self::Replace => glow::REPLACE,
+            Self::Increment => glow::INCR,
+            Self::IncrementWrap => glow::INCR_WRAP,
+            Self::Decrement => glow::DECR,
+            Self::DecrementWrap => glow::DECR_WRAP,
+            Self::Invert => glow::INVERT,
+        }
+    }
+}
+
 pub enum BuiltInShader {
     TexQuadVert,
     TexQuadFrag,
+    TexQuadCutoutFrag,
+    LineVert,
+    LineFrag,
 }
 
 pub struct GraphicsCommandInterface {
@@ -517,6 +1481,12 @@ impl GraphicsCommandInterface {
         Self { cmds: Vec::new() }
     }
 
+    // takes ownership of an existing buffer so its allocation can be reused, must be empty
+    pub fn from_raw(cmds: Vec<GraphicsCommand>) -> Self {
+        debug_assert!(cmds.is_empty());
+        Self { cmds }
+    }
+
     pub fn into_raw(self) -> Vec<GraphicsCommand> {
         self.cmds
     }
@@ -538,25 +1508,98 @@ impl GraphicsCommandInterface {
     }
 
     pub fn create_texture_rgb8(&mut self, filepath: PathBuf) -> Uuid {
+        self.create_texture_rgb8_with(filepath, TextureCreateOptions::default())
+    }
+    // the same file created with different options is loaded into separate textures
+    pub fn create_texture_rgb8_with(
+        &mut self,
+        filepath: PathBuf,
+        options: TextureCreateOptions,
+    ) -> Uuid {
         let id = Uuid::new_v4();
-        self.cmds
-            .push(GraphicsCommand::CreateTextureRGB8 { id, filepath });
+        self.cmds.push(GraphicsCommand::CreateTextureRGB8 {
+            id,
+            filepath,
+            options,
+        });
+        id
+    }
+    // preloads keep a ref that's never handed out, so the resource stays loaded for the
+    // lifetime of the graphics system and later creates of the same asset are free
+    pub fn preload_texture_rgb8(&mut self, filepath: PathBuf) {
+        self.create_texture_rgb8(filepath);
+    }
+    pub fn preload_texture_rgba8(&mut self, filepath: PathBuf, alpha_mode: AlphaMode) {
+        self.create_texture_rgba8(filepath, alpha_mode);
+    }
+    pub fn preload_shader(&mut self, sh_type: ShaderType, filename: impl ToString) {
+        self.create_shader(sh_type, filename);
+    }
+    pub fn preload_shader_builtin(&mut self, shader: BuiltInShader) {
+        self.create_shader_builtin(shader);
+    }
+    // the shaders are preloaded too, create_shader_program shares the program when it's given
+    // shaders created from the same files
+    pub fn preload_shader_program(&mut self, shaders: &[(ShaderType, &str)]) {
+        let shader_ids = shaders
+            .iter()
+            .map(|(sh_type, filename)| self.create_shader(*sh_type, filename))
+            .collect();
+        self.create_shader_program(shader_ids);
+    }
+
+    pub fn create_texture_rgba8(&mut self, filepath: PathBuf, alpha_mode: AlphaMode) -> Uuid {
+        self.create_texture_rgba8_with(filepath, alpha_mode, TextureCreateOptions::default())
+    }
+    pub fn create_texture_rgba8_with(
+        &mut self,
+        filepath: PathBuf,
+        alpha_mode: AlphaMode,
+        options: TextureCreateOptions,
+    ) -> Uuid {
+        let id = Uuid::new_v4();
+        self.cmds.push(GraphicsCommand::CreateTextureRGBA8 {
+            id,
+            filepath,
+            alpha_mode,
+            options,
+        });
         id
     }
     pub fn bind_texture(&mut self, id: Uuid, texture_slot: u8) {
         self.cmds
             .push(GraphicsCommand::BindTexture { id, texture_slot });
     }
+    // leaves the slot with no texture, so a shader sampling it doesn't pick up whatever the last
+    // draw bound there
+    pub fn unbind_texture(&mut self, texture_slot: u8) {
+        self.cmds
+            .push(GraphicsCommand::UnbindTexture { texture_slot });
+    }
     pub fn delete_texture(&mut self, id: Uuid) {
         self.cmds.push(GraphicsCommand::DeleteTexture { id });
     }
 
     pub fn create_shader(&mut self, sh_type: ShaderType, filename: impl ToString) -> Uuid {
+        self.create_shader_with_defines(sh_type, filename, &[])
+    }
+    // each (name, value) becomes a `#define name value` after the #version line, so one file can
+    // be compiled into several variants. the same file with other defines is a separate shader.
+    pub fn create_shader_with_defines(
+        &mut self,
+        sh_type: ShaderType,
+        filename: impl ToString,
+        defines: &[(&str, &str)],
+    ) -> Uuid {
         let id = Uuid::new_v4();
         self.cmds.push(GraphicsCommand::CreateShader {
             id,
             sh_type,
             filename: filename.to_string(),
+            defines: defines
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
         });
         id
     }
@@ -587,6 +1630,10 @@ impl GraphicsCommandInterface {
     pub fn draw_elements(&mut self, count: u32) {
         self.cmds.push(GraphicsCommand::DrawElements { count });
     }
+    // draws the bound vertex source's indices as pairs of line end points
+    pub fn draw_lines(&mut self, count: u32) {
+        self.cmds.push(GraphicsCommand::DrawLines { count });
+    }
 
     pub fn create_uniform_link(&mut self, program_id: Uuid, name: impl ToString) -> Uuid {
         let id = Uuid::new_v4();
@@ -602,4 +1649,309 @@ impl GraphicsCommandInterface {
         self.cmds
             .push(GraphicsCommand::UploadUniformMat4 { id, data });
     }
+    pub fn upload_uniform_vec4(&mut self, id: Uuid, data: glam::Vec4) {
+        self.cmds
+            .push(GraphicsCommand::UploadUniformVec4 { id, data });
+    }
+    pub fn upload_uniform_vec2(&mut self, id: Uuid, data: glam::Vec2) {
+        self.cmds
+            .push(GraphicsCommand::UploadUniformVec2 { id, data });
+    }
+    pub fn upload_uniform_vec3(&mut self, id: Uuid, data: glam::Vec3) {
+        self.cmds
+            .push(GraphicsCommand::UploadUniformVec3 { id, data });
+    }
+    pub fn upload_uniform_f32(&mut self, id: Uuid, data: f32) {
+        self.cmds
+            .push(GraphicsCommand::UploadUniformF32 { id, data });
+    }
+    pub fn upload_uniform_i32(&mut self, id: Uuid, data: i32) {
+        self.cmds
+            .push(GraphicsCommand::UploadUniformI32 { id, data });
+    }
+
+    // stencil state is applied in command order and persists between draws within a frame,
+    // the renderer disables the stencil test once the frame's commands are done so that
+    // it doesn't leak into the debug ui pass.
+    pub fn set_stencil_test(&mut self, enabled: bool) {
+        self.cmds.push(GraphicsCommand::SetStencilTest { enabled });
+    }
+    pub fn stencil_func(&mut self, func: StencilFunction, reference: i32, mask: u32) {
+        self.cmds.push(GraphicsCommand::StencilFunc {
+            func,
+            reference,
+            mask,
+        });
+    }
+    pub fn stencil_op(
+        &mut self,
+        stencil_fail: StencilOperation,
+        depth_fail: StencilOperation,
+        pass: StencilOperation,
+    ) {
+        self.cmds.push(GraphicsCommand::StencilOp {
+            stencil_fail,
+            depth_fail,
+            pass,
+        });
+    }
+    pub fn clear_stencil(&mut self, value: i32) {
+        self.cmds.push(GraphicsCommand::ClearStencil { value });
+    }
+
+    // core profiles only guarantee a width of 1.0, wider lines are clamped to what the driver
+    // supports and a warning is logged the first time that happens
+    pub fn set_line_width(&mut self, width: f32) {
+        self.cmds.push(GraphicsCommand::SetLineWidth { width });
+    }
+    // glow has no glPointSize, so point sizes come from gl_PointSize in the vertex shader
+    // which only takes effect while this is enabled
+    pub fn set_program_point_size(&mut self, enabled: bool) {
+        self.cmds
+            .push(GraphicsCommand::SetProgramPointSize { enabled });
+    }
+
+    // coverage comes from the fragment's alpha, which only does anything with a multisampled
+    // framebuffer (CreationArgs::msaa_samples). reset at the end of each frame.
+    pub fn set_alpha_to_coverage(&mut self, enabled: bool) {
+        self.cmds
+            .push(GraphicsCommand::SetAlphaToCoverage { enabled });
+    }
+
+    // an offscreen render target. the colour attachment is an rgba8 texture that can be bound
+    // with bind_texture once it's been drawn to, it belongs to the framebuffer and is deleted
+    // with it rather than by delete_texture.
+    pub fn create_framebuffer(
+        &mut self,
+        width: u32,
+        height: u32,
+        color_attachment: bool,
+        depth_attachment: bool,
+    ) -> FramebufferIds {
+        let ids = FramebufferIds {
+            framebuffer: Uuid::new_v4(),
+            color_texture: color_attachment.then(Uuid::new_v4),
+        };
+        self.cmds.push(GraphicsCommand::CreateFramebuffer {
+            id: ids.framebuffer,
+            width,
+            height,
+            color_attachment: ids.color_texture,
+            depth_attachment,
+        });
+        ids
+    }
+    // draws after this go into the framebuffer, which is given the whole viewport. it keeps what
+    // was drawn to it before, see clear_framebuffer.
+    pub fn bind_framebuffer(&mut self, id: Uuid) {
+        self.cmds.push(GraphicsCommand::BindFramebuffer { id });
+    }
+    // back to drawing to the window, with the viewport it had before the framebuffer was bound
+    pub fn unbind_framebuffer(&mut self) {
+        self.cmds.push(GraphicsCommand::UnbindFramebuffer);
+    }
+    // clears the colour and depth of the bound framebuffer, or of the window when none is bound.
+    // colour is rgba, stencil has clear_stencil.
+    pub fn clear_framebuffer(&mut self, colour: glam::Vec4) {
+        self.cmds.push(GraphicsCommand::ClearFramebuffer { colour });
+    }
+    pub fn delete_framebuffer(&mut self, id: Uuid) {
+        self.cmds.push(GraphicsCommand::DeleteFramebuffer { id });
+    }
+
+    // draws the texture over the whole viewport using a cached ndc quad
+    pub fn blit_fullscreen(&mut self, texture_id: Uuid, program_id: Uuid) {
+        self.cmds.push(GraphicsCommand::BlitFullscreen {
+            texture_id,
+            program_id,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        camera::Angle,
+        entity_component::{Component, Persistent},
+        graphics::component::TexQuadBase,
+    };
+
+    // runs the create/delete half of an update and turns the result into render commands, the
+    // way a frame does
+    fn frame(gfx: &mut GraphicsSystem, ents: &mut EntityTable) -> Vec<RenderCommand> {
+        let camera = ScreenCamera::new(Angle::rad(1.0), 1.0, (0.1, 100.0));
+        let mut k9cmd = GraphicsCommandInterface::new();
+        gfx.track_entities(ents, &mut k9cmd, &camera);
+        gfx.graphics_commands.append(&mut k9cmd.into_raw());
+
+        let mut rval = Vec::new();
+        gfx.get_render_commands(&mut rval);
+        ents.clear_deleted();
+        rval
+    }
+
+    fn ids_with<T: Component>(ents: &EntityTable) -> BTreeSet<Uuid> {
+        ents.get_by_component::<T>()
+            .map(|x| x.into_keys().collect())
+            .unwrap_or_default()
+    }
+
+    // adds ent and returns its id in the table, T is a component it has
+    fn add<T: Component>(ents: &mut EntityTable, ent: Entity) -> Uuid {
+        let before = ids_with::<T>(ents);
+        ents.add_new_entity(ent);
+        ids_with::<T>(ents)
+            .difference(&before)
+            .next()
+            .copied()
+            .unwrap()
+    }
+
+    fn add_quad(ents: &mut EntityTable, texture: &Path) -> Uuid {
+        let mut ent = Entity::new();
+        ent.add_component(GraphicsComponent::TexQuad(TexQuadBase::new(texture)));
+        add::<GraphicsComponent>(ents, ent)
+    }
+
+    fn add_transform(ents: &mut EntityTable, translation: glam::Vec3) -> Uuid {
+        let mut ent = Entity::new();
+        ent.add_component(TransformComponent::new(glam::Mat4::from_translation(
+            translation,
+        )));
+        add::<TransformComponent>(ents, ent)
+    }
+
+    fn world_translation(ents: &EntityTable, id: Uuid) -> glam::Vec3 {
+        let transform = ents
+            .get_by_uuid(&id)
+            .unwrap()
+            .get_component::<TransformComponent>();
+        transform.unwrap().world().w_axis.truncate()
+    }
+
+    fn count(cmds: &[RenderCommand], f: impl Fn(&RenderCommand) -> bool) -> usize {
+        cmds.iter().filter(|x| f(x)).count()
+    }
+
+    fn test_texture() -> PathBuf {
+        let path = std::env::temp_dir().join(format!("k9_test_{}.png", Uuid::new_v4()));
+        image::RgbImage::new(2, 2).save(&path).unwrap();
+        path
+    }
+
+    #[test]
+    fn create_and_delete_in_the_same_frame() {
+        let texture = test_texture();
+        let mut gfx = GraphicsSystem::new();
+        let mut ents = EntityTable::new();
+
+        let id = add_quad(&mut ents, &texture);
+        ents.delete_entity(&id);
+        let cmds = frame(&mut gfx, &mut ents);
+
+        assert!(cmds.is_empty(), "got {} commands", cmds.len());
+        assert!(gfx.tracked.is_empty());
+        assert!(gfx.texture_store.ref_counts.is_empty());
+        assert!(gfx.shader_store.ref_counts.is_empty());
+
+        std::fs::remove_file(texture).unwrap();
+    }
+
+    #[test]
+    fn shared_texture_lives_until_its_last_ref() {
+        let texture = test_texture();
+        let mut gfx = GraphicsSystem::new();
+        let mut ents = EntityTable::new();
+
+        let a = add_quad(&mut ents, &texture);
+        let b = add_quad(&mut ents, &texture);
+        let cmds = frame(&mut gfx, &mut ents);
+        let real_id = match cmds
+            .iter()
+            .find(|x| matches!(x, RenderCommand::CreateTextureRGB8 { .. }))
+        {
+            Some(RenderCommand::CreateTextureRGB8 { id, .. }) => *id,
+            _ => panic!("no texture created"),
+        };
+        assert_eq!(
+            count(&cmds, |x| matches!(
+                x,
+                RenderCommand::CreateTextureRGB8 { .. }
+            )),
+            1
+        );
+        assert_eq!(
+            count(&cmds, |x| matches!(x, RenderCommand::CreateShader { .. })),
+            2
+        );
+        assert_eq!(gfx.texture_store.ref_counts.get(&real_id), Some(&2));
+        assert_eq!(gfx.tracked, BTreeSet::from([a, b]));
+
+        ents.delete_entity(&a);
+        let cmds = frame(&mut gfx, &mut ents);
+        assert_eq!(
+            count(&cmds, |x| matches!(x, RenderCommand::DeleteTexture { .. })),
+            0
+        );
+        assert_eq!(
+            count(&cmds, |x| matches!(
+                x,
+                RenderCommand::DeleteVertexSource { .. }
+            )),
+            1
+        );
+        assert_eq!(gfx.texture_store.ref_counts.get(&real_id), Some(&1));
+
+        // one that comes and goes in the same frame doesn't touch the shared texture
+        let c = add_quad(&mut ents, &texture);
+        ents.delete_entity(&c);
+        ents.delete_entity(&b);
+        let cmds = frame(&mut gfx, &mut ents);
+        assert_eq!(
+            count(&cmds, |x| matches!(
+                x,
+                RenderCommand::CreateTextureRGB8 { .. }
+            )),
+            0
+        );
+        assert_eq!(
+            count(
+                &cmds,
+                |x| matches!(x, RenderCommand::DeleteTexture { id } if *id == real_id)
+            ),
+            1
+        );
+        assert_eq!(
+            count(&cmds, |x| matches!(x, RenderCommand::DeleteShader { .. })),
+            2
+        );
+        assert!(gfx.tracked.is_empty());
+        assert!(gfx.texture_store.ref_counts.is_empty());
+        assert!(gfx.texture_store.ref_real_map.is_empty());
+        assert!(gfx.texture_store.path_real_map.is_empty());
+
+        std::fs::remove_file(texture).unwrap();
+    }
+
+    #[test]
+    fn transforms_propagate_down_the_hierarchy() {
+        let mut ents = EntityTable::new();
+        let root = add_transform(&mut ents, glam::vec3(1.0, 0.0, 0.0));
+        // no transform of its own, its children are placed relative to root
+        let mut ent = Entity::new();
+        ent.add_component(Persistent);
+        let middle = add::<Persistent>(&mut ents, ent);
+        let leaf = add_transform(&mut ents, glam::vec3(0.0, 2.0, 0.0));
+        ents.set_parent(middle, root).unwrap();
+        ents.set_parent(leaf, middle).unwrap();
+
+        propagate_transforms(&mut ents);
+        assert_eq!(world_translation(&ents, root), glam::vec3(1.0, 0.0, 0.0));
+        assert_eq!(world_translation(&ents, leaf), glam::vec3(1.0, 2.0, 0.0));
+
+        ents.detach(middle);
+        propagate_transforms(&mut ents);
+        assert_eq!(world_translation(&ents, leaf), glam::vec3(0.0, 2.0, 0.0));
+    }
 }
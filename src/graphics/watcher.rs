@@ -0,0 +1,153 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    sync::mpsc,
+    time::{Duration, Instant},
+};
+
+use notify::Watcher;
+
+use crate::resolve_asset;
+
+// a file has to stop changing for this long before it's reported, editors often save in several
+// writes and a half written file would fail to load
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+// watches asset files with notify. the directories holding them are watched rather than the files
+// themselves, editors often save by replacing the file which would drop a watch on it.
+pub(crate) struct AssetWatcher {
+    watcher: notify::RecommendedWatcher,
+    events: mpsc::Receiver<notify::Result<notify::Event>>,
+    dirs: BTreeSet<PathBuf>,
+    // path as the graphics system gave it to where notify reports its changes, see watched_path
+    files: BTreeMap<PathBuf, PathBuf>,
+    // changed files not reported yet, with when they last changed
+    pending: BTreeMap<PathBuf, Instant>,
+}
+
+impl AssetWatcher {
+    pub fn new() -> Result<Self, String> {
+        let (sender, events) = mpsc::channel();
+        let watcher = notify::recommended_watcher(sender).map_err(|e| e.to_string())?;
+        Ok(Self {
+            watcher,
+            events,
+            dirs: BTreeSet::new(),
+            files: BTreeMap::new(),
+            pending: BTreeMap::new(),
+        })
+    }
+
+    // paths whose files changed and have settled since they were last reported. paths are taken
+    // as they were given to the graphics system, only changes after a path is first passed in
+    // are seen.
+    pub fn poll<'a>(&mut self, paths: impl Iterator<Item = &'a Path>) -> Vec<PathBuf> {
+        self.update_watched(paths);
+
+        let now = Instant::now();
+        for event in self.events.try_iter() {
+            let event = match event {
+                Ok(x) => x,
+                Err(e) => {
+                    log::warn!("asset watcher error: {e}");
+                    continue;
+                }
+            };
+            if !(event.kind.is_create() || event.kind.is_modify()) {
+                continue;
+            }
+            for (path, watched) in &self.files {
+                if event.paths.contains(watched) {
+                    self.pending.insert(path.clone(), now);
+                }
+            }
+        }
+
+        let settled: Vec<PathBuf> = self
+            .pending
+            .iter()
+            .filter(|(_, changed_at)| now.duration_since(**changed_at) >= DEBOUNCE)
+            .map(|(path, _)| path.clone())
+            .collect();
+        for path in &settled {
+            self.pending.remove(path);
+        }
+        settled
+    }
+
+    // watches the directories of paths and stops watching any no longer needed. anything not
+    // passed in is no longer loaded, so it's forgotten.
+    fn update_watched<'a>(&mut self, paths: impl Iterator<Item = &'a Path>) {
+        let mut files = BTreeMap::new();
+        for path in paths {
+            let watched = self.files.remove(path).or_else(|| watched_path(path));
+            if let Some(watched) = watched {
+                files.insert(path.to_path_buf(), watched);
+            }
+        }
+        self.files = files;
+
+        let dirs: BTreeSet<PathBuf> = self
+            .files
+            .values()
+            .filter_map(|x| x.parent())
+            .map(Path::to_path_buf)
+            .collect();
+        for dir in dirs.difference(&self.dirs) {
+            if let Err(e) = self.watcher.watch(dir, notify::RecursiveMode::NonRecursive) {
+                log::warn!("couldn't watch {dir:?} for asset changes: {e}");
+            }
+        }
+        for dir in self.dirs.difference(&dirs) {
+            // fails when the directory is gone, which ends the watch anyway
+            let _ = self.watcher.unwatch(dir);
+        }
+        self.dirs = dirs;
+
+        self.pending.retain(|path, _| self.files.contains_key(path));
+    }
+}
+
+// the path notify reports changes to the file under, within its canonical directory. None when
+// the directory doesn't exist.
+fn watched_path(path: &Path) -> Option<PathBuf> {
+    let resolved = resolve_asset(path);
+    let dir = match resolved.parent() {
+        Some(x) if !x.as_os_str().is_empty() => x,
+        _ => Path::new("."),
+    };
+    Some(std::fs::canonicalize(dir).ok()?.join(resolved.file_name()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_a_change_once_it_settles() {
+        let dir = std::env::temp_dir().join(format!("k9_watch_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir(&dir).unwrap();
+        let path = dir.join("a.glsl");
+        std::fs::write(&path, "a").unwrap();
+
+        let mut watcher = AssetWatcher::new().unwrap();
+        // the same file passed twice, e.g. a texture loaded in two formats, is one file
+        let paths = [path.as_path(), path.as_path()];
+        assert!(watcher.poll(paths.into_iter()).is_empty());
+
+        std::fs::write(&path, "b").unwrap();
+        let start = Instant::now();
+        let mut changed = Vec::new();
+        while changed.is_empty() && start.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(50));
+            changed = watcher.poll(paths.into_iter());
+            if !changed.is_empty() {
+                assert!(start.elapsed() >= DEBOUNCE);
+            }
+        }
+        assert_eq!(changed, vec![path.clone()]);
+        assert!(watcher.poll(paths.into_iter()).is_empty());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,110 @@
+use std::collections::HashSet;
+
+use sdl2::{event::Event, keyboard::Keycode};
+
+#[derive(Default)]
+pub struct KeyboardState {
+    held: HashSet<Keycode>,
+    pressed: HashSet<Keycode>,
+    released: HashSet<Keycode>,
+}
+impl KeyboardState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update(&mut self, sdl_events: &Vec<Event>) {
+        self.pressed.clear();
+        self.released.clear();
+
+        for event in sdl_events {
+            match event {
+                Event::KeyDown {
+                    keycode: Some(kc),
+                    repeat,
+                    ..
+                } => {
+                    if !*repeat {
+                        self.pressed.insert(*kc);
+                    }
+                    self.held.insert(*kc);
+                }
+                Event::KeyUp {
+                    keycode: Some(kc), ..
+                } => {
+                    self.released.insert(*kc);
+                    self.held.remove(kc);
+                }
+                Event::Window {
+                    win_event: sdl2::event::WindowEvent::FocusLost,
+                    ..
+                } => {
+                    // we won't get the key ups while unfocused, avoid stuck keys
+                    self.held.clear();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub fn is_key_down(&self, keycode: Keycode) -> bool {
+        self.held.contains(&keycode)
+    }
+
+    pub fn is_key_pressed(&self, keycode: Keycode) -> bool {
+        self.pressed.contains(&keycode)
+    }
+
+    pub fn is_key_released(&self, keycode: Keycode) -> bool {
+        self.released.contains(&keycode)
+    }
+
+    pub fn held_keys(&self) -> &HashSet<Keycode> {
+        &self.held
+    }
+}
+
+#[derive(Default)]
+pub struct MouseState {
+    position: (i32, i32),
+    delta: (i32, i32),
+    wants_relative_mode: bool,
+}
+impl MouseState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update(&mut self, sdl_events: &Vec<Event>) {
+        self.delta = (0, 0);
+
+        for event in sdl_events {
+            if let Event::MouseMotion {
+                x, y, xrel, yrel, ..
+            } = event
+            {
+                self.position = (*x, *y);
+                self.delta.0 += *xrel;
+                self.delta.1 += *yrel;
+            }
+        }
+    }
+
+    pub fn position(&self) -> (i32, i32) {
+        self.position
+    }
+
+    // accumulated motion this frame, this is the value to use while in relative mode
+    pub fn delta(&self) -> (i32, i32) {
+        self.delta
+    }
+
+    // hides and locks the cursor, suspended while the debug ui is open
+    pub fn set_relative_mode(&mut self, enabled: bool) {
+        self.wants_relative_mode = enabled;
+    }
+
+    pub fn wants_relative_mode(&self) -> bool {
+        self.wants_relative_mode
+    }
+}
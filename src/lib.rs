@@ -7,10 +7,15 @@
 pub mod process;
 pub use process::run;
 
+pub mod asset;
+pub use asset::resolve_asset;
+
 pub mod entity_component;
 pub use entity_component::EntityTable;
 pub mod graphics;
+pub mod input;
 mod profile;
+mod shaders;
 pub mod system;
 pub use system::System;
 pub use system::SystemCallbacks;
@@ -21,3 +26,4 @@ pub use k9_proc_macros::console_command;
 
 pub use egui;
 pub use egui_extras;
+pub use glow;
@@ -1,5 +1,6 @@
 use std::{
     collections::BTreeMap,
+    path::PathBuf,
     sync::{Arc, Mutex},
     time::{Duration, Instant},
 };
@@ -8,12 +9,14 @@ use glow::HasContext;
 use k9_proc_macros::console_command_internal;
 
 use crate::{
+    asset,
     camera::{Angle, ScreenCamera},
-    debug_ui::{self, EguiDebugUi},
-    entity_component::{Entity, EntityTable},
+    debug_ui::{self, ConsoleTheme, DebugUiBanner, EguiDebugUi, LogLevelTheme},
+    entity_component::{Entity, EntityTable, Persistent},
     graphics::{GraphicsSystem, K9Renderer},
+    input::{KeyboardState, MouseState},
     profile::ProfileSet,
-    system::{FirstCallState, FrameState, SystemCallbacks},
+    system::{FirstCallState, FrameRequests, FrameState, RawContext, SystemCallbacks},
 };
 
 pub struct CreationArgs {
@@ -24,6 +27,39 @@ pub struct CreationArgs {
     pub use_vsync: bool,
     pub dimensions: (u32, u32),
     pub fullscreen: bool,
+    pub pause_on_focus_loss: bool,
+    pub render_while_paused: bool,
+    // frame cap while the window is unfocused, None or Some(0) keeps max_fps
+    pub idle_max_fps: Option<u32>,
+    pub resizable: bool,
+    pub borderless: bool,
+    pub always_on_top: bool,
+    pub start_maximized: bool,
+    pub start_minimized: bool,
+    // the window is centered on this display, None (or an index that doesn't exist) uses the
+    // primary display
+    pub display_index: Option<i32>,
+    // relative texture/shader paths are resolved against this, None uses the working directory
+    pub asset_root: Option<PathBuf>,
+    // reloads textures and shaders loaded from files when they change on disk, debug builds only
+    pub watch_assets: bool,
+    pub debug_ui_banner: DebugUiBanner,
+    // samples per pixel for the window's framebuffer, 0 disables multisampling
+    pub msaa_samples: u8,
+    // renders the debug ui to its own texture, see FrameState::debug_ui_texture
+    pub debug_ui_offscreen: bool,
+    // debug windows' serialized states and the banner's opacity/scale are loaded from here at
+    // startup and saved on exit
+    pub debug_ui_state_path: Option<PathBuf>,
+    // console commands are loaded from here at startup and appended as they're run, None keeps
+    // the history in memory only
+    pub console_history_path: Option<PathBuf>,
+    // mirrors records up to this level to stderr with the console's colours, None disables it
+    pub terminal_log_level: Option<log::Level>,
+    // the debug ui's palette, the set_theme command can switch to a bundled preset at runtime
+    pub console_theme: ConsoleTheme,
+    // level colours for the console log, also used by the terminal logger
+    pub log_level_theme: LogLevelTheme,
 }
 impl Default for CreationArgs {
     fn default() -> Self {
@@ -35,6 +71,25 @@ impl Default for CreationArgs {
             use_vsync: true,
             window_title: "k9 window".to_owned(),
             fullscreen: false,
+            pause_on_focus_loss: false,
+            render_while_paused: true,
+            idle_max_fps: None,
+            resizable: false,
+            borderless: false,
+            always_on_top: false,
+            start_maximized: false,
+            start_minimized: false,
+            display_index: None,
+            asset_root: None,
+            watch_assets: false,
+            debug_ui_banner: DebugUiBanner::default(),
+            msaa_samples: 0,
+            debug_ui_offscreen: false,
+            debug_ui_state_path: None,
+            console_history_path: None,
+            terminal_log_level: None,
+            console_theme: ConsoleTheme::default(),
+            log_level_theme: LogLevelTheme::default(),
         }
     }
 }
@@ -49,11 +104,20 @@ pub fn run(args: Option<CreationArgs>) -> Result<(), String> {
     let mut loggers = args.loggers;
     let dbg_console_logger = debug_ui::DebugConsoleLogger::new();
     let dbg_logger_shared = dbg_console_logger.get_shared();
+    let dbg_log_queue = dbg_console_logger.get_queue();
     loggers.push(Box::new(dbg_console_logger));
+    if let Some(level) = args.terminal_log_level {
+        loggers.push(Box::new(debug_ui::TerminalLogger::new(
+            level,
+            args.log_level_theme.clone(),
+        )));
+    }
 
     multi_log::MultiLogger::init(loggers, log::Level::Trace)
         .map_err(|e| format!("couldn't initialize logger: {e}"))?;
 
+    asset::set_asset_root(args.asset_root);
+
     // init entities
     let mut entities = EntityTable::new();
 
@@ -69,11 +133,46 @@ pub fn run(args: Option<CreationArgs>) -> Result<(), String> {
     gl_attr.set_context_major_version(3);
     gl_attr.set_context_minor_version(3);
     gl_attr.set_context_profile(sdl2::video::GLProfile::Core);
+    gl_attr.set_depth_size(24);
+    gl_attr.set_stencil_size(8);
+    gl_attr.set_framebuffer_srgb_compatible(true);
+    if args.msaa_samples > 0 {
+        gl_attr.set_multisample_buffers(1);
+        gl_attr.set_multisample_samples(args.msaa_samples);
+    }
+
+    let mut sdl_wnd_builder = sdl_vss.window("k9 window", args.dimensions.0, args.dimensions.1);
+    sdl_wnd_builder.opengl();
+    match display_bounds(&sdl_vss, args.display_index) {
+        Some(bounds) => {
+            sdl_wnd_builder.position(
+                bounds.x() + (bounds.width() as i32 - args.dimensions.0 as i32) / 2,
+                bounds.y() + (bounds.height() as i32 - args.dimensions.1 as i32) / 2,
+            );
+        }
+        None => {
+            sdl_wnd_builder.position_centered();
+        }
+    }
+    if args.resizable {
+        sdl_wnd_builder.resizable();
+    }
+    if args.borderless {
+        sdl_wnd_builder.borderless();
+    }
+    if args.start_maximized {
+        sdl_wnd_builder.maximized();
+    }
+    if args.start_minimized {
+        sdl_wnd_builder.minimized();
+    }
+    if args.always_on_top {
+        let flags = sdl_wnd_builder.window_flags()
+            | sdl2::sys::SDL_WindowFlags::SDL_WINDOW_ALWAYS_ON_TOP as u32;
+        sdl_wnd_builder.set_window_flags(flags);
+    }
 
-    let mut sdl_wnd = sdl_vss
-        .window("k9 window", args.dimensions.0, args.dimensions.1)
-        .opengl()
-        .position_centered()
+    let mut sdl_wnd = sdl_wnd_builder
         .build()
         .map_err(|e| format!("couldn't create window: {e}"))?;
 
@@ -103,21 +202,56 @@ pub fn run(args: Option<CreationArgs>) -> Result<(), String> {
         })
         .map_err(|e| format!("couldn't set swap interval: {e}"))?;
 
+    // read by the gl debug callback, changed by the gl_debug command
+    let gl_debug_settings = Arc::new(Mutex::new(GlDebugSettings {
+        level: GlDebugLevel::Notification,
+        synchronous: false,
+    }));
+    let mut gl_debug_synchronous = false;
+
     unsafe {
         // todo: this probably needs to be screen scaled
         glow.viewport(0, 0, args.dimensions.0 as i32, args.dimensions.1 as i32);
         glow.enable(glow::DEBUG_OUTPUT);
-        glow.debug_message_callback(debug_callback);
+        let settings = gl_debug_settings.clone();
+        glow.debug_message_callback(move |src, ty, id, severity, msg| {
+            if settings.lock().unwrap().level.forwards(severity) {
+                debug_callback(src, ty, id, severity, msg);
+            }
+        });
     }
 
+    if args.msaa_samples > 0 {
+        let samples = gl_attr.multisample_samples();
+        if samples != args.msaa_samples {
+            log::warn!(
+                "requested {} msaa samples, got {samples}",
+                args.msaa_samples
+            );
+        }
+        unsafe { glow.enable(glow::MULTISAMPLE) };
+    }
+
+    // driver info, logged now since it's the first thing needed for a bug report
+    let gl_info = unsafe { GlInfo::query(&glow) };
+    log::info!("{gl_info}");
+
     let mut sdl_ep = sdl_ctx
         .event_pump()
         .map_err(|e| format!("couldn't create event pump: {e}"))?;
 
     sdl_wnd.show();
 
-    let mut k9 = K9Renderer::new().map_err(|e| format!("couldn't init graphics renderer: {e}"))?;
+    let mut k9 =
+        K9Renderer::new(&glow).map_err(|e| format!("couldn't init graphics renderer: {e}"))?;
     let mut gfx_system = GraphicsSystem::new();
+    if args.watch_assets {
+        if cfg!(debug_assertions) {
+            gfx_system.watch_assets();
+        } else {
+            log::warn!("watch_assets is ignored in release builds");
+        }
+    }
 
     #[allow(unused_assignments)] // is used in log::info
     let mut is_frame_capped = false;
@@ -132,7 +266,17 @@ pub fn run(args: Option<CreationArgs>) -> Result<(), String> {
     let aspect_ratio = args.dimensions.0 as f32 / args.dimensions.1 as f32;
     let mut screen_camera = ScreenCamera::new(Angle::deg(45.0), aspect_ratio, (100.0, 5_000.0));
 
-    let screen_dimensions = args.dimensions;
+    let mut screen_dimensions = args.dimensions;
+    let window_id = sdl_wnd.id();
+    let mut window_focused = is_window_focused(&sdl_wnd);
+    let mut keyboard = KeyboardState::new();
+    let mut mouse = MouseState::new();
+    let mut frame_requests = FrameRequests::default();
+    let mut picked_entity = None;
+    let mut debug_ui_texture = None;
+    let mut draw_debug_ui = false;
+    let sdl_mouse = sdl_ctx.mouse();
+    let raw_context = RawContext::new(&glow, &sdl_wnd, &sdl_vss);
     let system_scale = {
         match sdl_wnd.display_index() {
             Ok(x) => match sdl_vss.display_dpi(x) {
@@ -163,7 +307,101 @@ pub fn run(args: Option<CreationArgs>) -> Result<(), String> {
         assert!(console_commands.insert("quit".to_owned(), cc).is_none());
     }
 
-    let mut current_render_commands = Some(Vec::new());
+    // version command
+    {
+        let gl_version = gl_info.version.clone();
+        let gl_renderer = gl_info.renderer.clone();
+        let sdl_version = sdl2::version::version();
+        let build_profile = if cfg!(debug_assertions) {
+            "debug"
+        } else {
+            "release"
+        };
+        let cc = console_command_internal!(
+            "prints the k9 version, build profile, sdl version and graphics driver info.",
+            {},
+            |_| {
+                log::info!(
+                    "k9 {} ({build_profile})\nsdl {sdl_version}\ngl {gl_version}\nrenderer {gl_renderer}",
+                    env!("CARGO_PKG_VERSION"),
+                );
+                Ok(())
+            }
+        );
+        assert!(console_commands.insert("version".to_owned(), cc).is_none());
+    }
+
+    // gpuinfo command
+    {
+        let cc = console_command_internal!(
+            "prints the gl vendor, renderer, version and shading language version.",
+            {},
+            |_| {
+                log::info!("{gl_info}");
+                Ok(())
+            }
+        );
+        assert!(console_commands.insert("gpuinfo".to_owned(), cc).is_none());
+    }
+
+    // gl_debug command
+    {
+        let settings = gl_debug_settings.clone();
+        let cc = console_command_internal!(
+            "sets which gl debug messages are logged: off, high, medium, low or notification. sync makes messages fire during the call that caused them.",
+            { level: String, opt sync: bool },
+            |ccf, level: String, sync| {
+                let level = GlDebugLevel::from_name(&level).ok_or(format!(
+                    "unknown gl debug level '{level}', expected off, high, medium, low or notification"
+                ))?;
+                let mut settings = settings.lock().unwrap();
+                settings.level = level;
+                if let Some(sync) = sync {
+                    settings.synchronous = sync;
+                }
+                Ok(())
+            }
+        );
+        assert!(console_commands.insert("gl_debug".to_owned(), cc).is_none());
+    }
+
+    // reset_scene command
+    let reset_scene_request = Arc::new(Mutex::new(false));
+    {
+        let flag = reset_scene_request.clone();
+        let cc = console_command_internal!(
+            "deletes every entity without a Persistent component and reruns the user systems' first call.",
+            {},
+            |_| {
+                *flag.lock().unwrap() = true;
+                Ok(())
+            }
+        );
+        assert!(console_commands
+            .insert("reset_scene".to_owned(), cc)
+            .is_none());
+    }
+
+    // dump_entities command
+    let dump_entities_request = Arc::new(Mutex::new(false));
+    {
+        let flag = dump_entities_request.clone();
+        let cc = console_command_internal!(
+            "logs every entity's id and component names, click the record to see the full list.",
+            {},
+            |_| {
+                *flag.lock().unwrap() = true;
+                Ok(())
+            }
+        );
+        assert!(console_commands
+            .insert("dump_entities".to_owned(), cc)
+            .is_none());
+    }
+
+    // render commands are double buffered, the commands generated this frame are drawn next frame
+    let mut current_render_commands = Vec::new();
+    let mut next_render_commands = Vec::new();
 
     let mut profile_update_time = Instant::now();
 
@@ -171,6 +409,28 @@ pub fn run(args: Option<CreationArgs>) -> Result<(), String> {
 
     // do first calls for systems
     let mut debug_windows = BTreeMap::new();
+    gfx_system.first_call(
+        FirstCallState {
+            console_commands: &mut console_commands,
+            debug_windows: &mut debug_windows,
+        },
+        FrameState {
+            ents: &mut entities,
+            sdl_events: &sdl_events,
+            screen_camera: &mut screen_camera,
+            screen_dimensions,
+            screen_scale: system_scale,
+            window_id,
+            window_focused,
+            keyboard: &keyboard,
+            mouse: &mut mouse,
+            raw: raw_context,
+            requests: &mut frame_requests,
+            picked_entity,
+            debug_ui_texture,
+            debug_ui_open: draw_debug_ui,
+        },
+    );
     for system in &mut user_systems {
         system.first_call(
             FirstCallState {
@@ -183,19 +443,110 @@ pub fn run(args: Option<CreationArgs>) -> Result<(), String> {
                 screen_camera: &mut screen_camera,
                 screen_dimensions,
                 screen_scale: system_scale,
+                window_id,
+                window_focused,
+                keyboard: &keyboard,
+                mouse: &mut mouse,
+                raw: raw_context,
+                requests: &mut frame_requests,
+                picked_entity,
+                debug_ui_texture,
+                debug_ui_open: draw_debug_ui,
             },
         );
     }
 
-    let mut draw_debug_ui = false;
-    let mut debug_ui = EguiDebugUi::new(&glow, system_scale, console_commands, debug_windows);
+    let mut debug_ui = EguiDebugUi::new(
+        &glow,
+        system_scale,
+        window_id,
+        console_commands,
+        debug_windows,
+        args.debug_ui_banner,
+        args.console_theme,
+        args.log_level_theme,
+        args.console_history_path,
+    );
+    debug_ui.set_render_offscreen(&glow, args.debug_ui_offscreen);
+    if let Some(path) = &args.debug_ui_state_path {
+        debug_ui.load_window_states(path);
+    }
 
     loop {
         // MAIN PROGRAM LOOP
-        sdl_events = sdl_ep.poll_iter().collect();
+        // only events for our window (or not tied to any window) are handed on
+        sdl_events = sdl_ep
+            .poll_iter()
+            .filter(|e| is_event_for_window(e, window_id))
+            .collect();
+
+        // handle window state before systems see the frame
+        window_focused = is_window_focused(&sdl_wnd);
+        keyboard.update(&sdl_events);
+        mouse.update(&sdl_events);
+        for event in &sdl_events {
+            if let sdl2::event::Event::Window {
+                timestamp: _,
+                window_id: _,
+                win_event: sdl2::event::WindowEvent::SizeChanged(w, h),
+            } = event
+            {
+                if *w > 0 && *h > 0 {
+                    screen_dimensions = (*w as u32, *h as u32);
+                    unsafe {
+                        glow.viewport(0, 0, *w, *h);
+                    }
+                    screen_camera.set_aspect_ratio(*w as f32 / *h as f32);
+                }
+            }
+        }
+
+        if std::mem::take(&mut *dump_entities_request.lock().unwrap()) {
+            log::info!("{} entities\n{}", entities.all().len(), entities.describe());
+        }
+
+        // deleted entities go through the usual delete path in the graphics system's next update.
+        // console commands and debug windows registered by the rerun first calls are dropped,
+        // the ones from startup stay registered.
+        if std::mem::take(&mut *reset_scene_request.lock().unwrap()) {
+            let deleted = entities.delete_all_without::<Persistent>();
+            log::info!("reset scene, deleted {deleted} entities");
+
+            for system in &mut user_systems {
+                system.first_call(
+                    FirstCallState {
+                        console_commands: &mut BTreeMap::new(),
+                        debug_windows: &mut BTreeMap::new(),
+                    },
+                    FrameState {
+                        ents: &mut entities,
+                        sdl_events: &sdl_events,
+                        screen_camera: &mut screen_camera,
+                        screen_dimensions,
+                        screen_scale: system_scale,
+                        window_id,
+                        window_focused,
+                        keyboard: &keyboard,
+                        mouse: &mut mouse,
+                        raw: raw_context,
+                        requests: &mut frame_requests,
+                        picked_entity,
+                        debug_ui_texture,
+                        debug_ui_open: draw_debug_ui,
+                    },
+                );
+            }
+        }
+
+        // the debug ui keeps running while paused so the console can still be used
+        let is_paused = args.pause_on_focus_loss && !window_focused;
+        let skip_render = is_paused && !args.render_while_paused && !draw_debug_ui;
 
         frame_profile.scoped_run(|| {
             user_systems_profile.scoped_run(|| {
+                if is_paused {
+                    return;
+                }
                 for system in &mut user_systems {
                     system.update(FrameState {
                         ents: &mut entities,
@@ -203,49 +554,117 @@ pub fn run(args: Option<CreationArgs>) -> Result<(), String> {
                         screen_camera: &mut screen_camera,
                         screen_dimensions,
                         screen_scale: system_scale,
+                        window_id,
+                        window_focused,
+                        keyboard: &keyboard,
+                        mouse: &mut mouse,
+                        raw: raw_context,
+                        requests: &mut frame_requests,
+                        picked_entity,
+                        debug_ui_texture,
+                        debug_ui_open: draw_debug_ui,
                     });
                 }
             });
 
-            let render_commands = rc_gen_profile.scoped_run(|| {
+            if skip_render {
+                return;
+            }
+
+            rc_gen_profile.scoped_run(|| {
                 gfx_system.update(FrameState {
                     ents: &mut entities,
                     sdl_events: &sdl_events,
                     screen_camera: &mut screen_camera,
                     screen_dimensions,
                     screen_scale: system_scale,
+                    window_id,
+                    window_focused,
+                    keyboard: &keyboard,
+                    mouse: &mut mouse,
+                    raw: raw_context,
+                    requests: &mut frame_requests,
+                    picked_entity,
+                    debug_ui_texture,
+                    debug_ui_open: draw_debug_ui,
                 });
-                gfx_system.get_render_commands()
+                // the graphics system has freed what the deleted entities held
+                entities.clear_deleted();
+                gfx_system.get_render_commands(&mut next_render_commands);
             });
 
             gfx_profile.scoped_run(|| {
-                #[cfg(not(debug_assertions))]
-                unsafe {
-                    k9.render(&glow, current_render_commands.take().unwrap_unchecked())
-                };
-                #[cfg(debug_assertions)]
-                k9.render(&glow, current_render_commands.take().unwrap());
+                k9.render(&glow, &mut current_render_commands);
             });
-            current_render_commands = Some(render_commands);
+            if let Some(pick) = gfx_system.resolve_picks(k9.take_pick_results()) {
+                picked_entity = pick;
+            }
+            std::mem::swap(&mut current_render_commands, &mut next_render_commands);
         });
 
+        if frame_requests.open_console {
+            frame_requests.open_console = false;
+            draw_debug_ui = true;
+            debug_ui.set_console_focus();
+        }
+
+        // needs the context, so it's applied here rather than in the gl_debug command
+        let synchronous = gl_debug_settings.lock().unwrap().synchronous;
+        if synchronous != gl_debug_synchronous {
+            gl_debug_synchronous = synchronous;
+            unsafe {
+                if synchronous {
+                    glow.enable(glow::DEBUG_OUTPUT_SYNCHRONOUS);
+                } else {
+                    glow.disable(glow::DEBUG_OUTPUT_SYNCHRONOUS);
+                }
+            }
+        }
+
+        // the debug ui needs a free cursor, so relative mode is dropped while it's open
+        let relative_mouse_mode = mouse.wants_relative_mode() && !draw_debug_ui;
+        if sdl_mouse.relative_mouse_mode() != relative_mouse_mode {
+            sdl_mouse.set_relative_mouse_mode(relative_mouse_mode);
+        }
+
+        dbg_log_queue.flush();
         if draw_debug_ui {
             debug_ui.render(
                 &glow,
                 &sdl_events,
                 &clipboard_util,
                 screen_dimensions,
-                sdl_wnd.window_flags() & sdl2::sys::SDL_WindowFlags::SDL_WINDOW_INPUT_FOCUS as u32
-                    != 0,
+                window_focused,
                 &dbg_logger_shared,
+                &mut |ctx| {
+                    for system in &mut user_systems {
+                        system.debug_draw(ctx);
+                    }
+                },
             );
+            debug_ui_texture = debug_ui.ui_texture();
+        } else {
+            debug_ui_texture = None;
         }
 
-        sdl_wnd.gl_swap_window();
+        if !skip_render {
+            sdl_wnd.gl_swap_window();
+        }
 
         for event in &sdl_events {
             match event {
                 sdl2::event::Event::Quit { timestamp: _ } => *is_finished.lock().unwrap() = true,
+                sdl2::event::Event::KeyDown {
+                    keycode: Some(kc),
+                    repeat: false,
+                    ..
+                } => {
+                    // only typing into the console holds keybinds back, they still fire with
+                    // the console open but unfocused
+                    if !(draw_debug_ui && debug_ui.console_has_focus()) {
+                        debug_ui.run_key_binding(*kc);
+                    }
+                }
                 sdl2::event::Event::KeyUp {
                     timestamp: _,
                     window_id: _,
@@ -273,10 +692,26 @@ pub fn run(args: Option<CreationArgs>) -> Result<(), String> {
 
         // handle max_fps / throttling
         // todo, this shit sucks and is hardly effective, tune this or find a different method of doing this.
-        let min_frame_time_micros = 1_000_000 / args.max_fps as i128;
-        let last_micros = unsafe { frame_profile.last().unwrap_unchecked().as_micros() as i128 };
+        // while unfocused the idle cap applies, if any input arrives we stop waiting
+        // so that the next frame runs right away at the active rate.
+        let idle_max_fps = args.idle_max_fps.filter(|x| *x != 0 && !window_focused);
+        let max_fps = idle_max_fps.unwrap_or(args.max_fps);
+        let min_frame_time_micros = 1_000_000 / max_fps as i128;
+        let last_frame = frame_profile.last().unwrap_or_default();
+        let last_micros = last_frame.as_micros() as i128;
+        debug_ui.push_frame_time(
+            last_frame,
+            Duration::from_micros(1_000_000 / args.max_fps as u64),
+        );
 
-        if last_micros < min_frame_time_micros {
+        if last_micros < min_frame_time_micros && idle_max_fps.is_some() {
+            is_frame_capped = true;
+            let wait_millis = (min_frame_time_micros - last_micros) / 1_000;
+            unsafe {
+                // a null event only peeks, the event stays queued for the next poll
+                sdl2::sys::SDL_WaitEventTimeout(std::ptr::null_mut(), wait_millis as i32);
+            }
+        } else if last_micros < min_frame_time_micros {
             is_frame_capped = true;
             let mut sleep_micros = min_frame_time_micros - last_micros;
             loop {
@@ -302,7 +737,7 @@ pub fn run(args: Option<CreationArgs>) -> Result<(), String> {
 
         // handle profiling
         let sample_time = 20;
-        if profile_update_time.elapsed().as_secs() >= sample_time {
+        if !skip_render && profile_update_time.elapsed().as_secs() >= sample_time {
             let mut fps_tag = "".to_owned();
             if args.use_vsync {
                 fps_tag += " [vsync]";
@@ -329,12 +764,16 @@ pub fn run(args: Option<CreationArgs>) -> Result<(), String> {
 
             gfx_profile.clear();
             rc_gen_profile.clear();
+            user_systems_profile.clear();
             frame_profile.clear();
             profile_update_time = Instant::now();
         }
 
         // handle shutdown
         if *is_finished.lock().unwrap() {
+            if let Some(path) = &args.debug_ui_state_path {
+                debug_ui.save_window_states(path);
+            }
             for system in &mut user_systems {
                 system.exiting(FrameState {
                     ents: &mut entities,
@@ -342,6 +781,15 @@ pub fn run(args: Option<CreationArgs>) -> Result<(), String> {
                     screen_camera: &mut screen_camera,
                     screen_dimensions,
                     screen_scale: system_scale,
+                    window_id,
+                    window_focused,
+                    keyboard: &keyboard,
+                    mouse: &mut mouse,
+                    raw: raw_context,
+                    requests: &mut frame_requests,
+                    picked_entity,
+                    debug_ui_texture,
+                    debug_ui_open: draw_debug_ui,
                 });
             }
             gfx_system.exiting(FrameState {
@@ -350,6 +798,15 @@ pub fn run(args: Option<CreationArgs>) -> Result<(), String> {
                 screen_camera: &mut screen_camera,
                 screen_dimensions,
                 screen_scale: system_scale,
+                window_id,
+                window_focused,
+                keyboard: &keyboard,
+                mouse: &mut mouse,
+                raw: raw_context,
+                requests: &mut frame_requests,
+                picked_entity,
+                debug_ui_texture,
+                debug_ui_open: draw_debug_ui,
             });
             break;
         }
@@ -358,6 +815,105 @@ pub fn run(args: Option<CreationArgs>) -> Result<(), String> {
     Ok(())
 }
 
+struct GlInfo {
+    vendor: String,
+    renderer: String,
+    version: String,
+    shading_language_version: String,
+}
+impl GlInfo {
+    unsafe fn query(glow: &glow::Context) -> Self {
+        Self {
+            vendor: glow.get_parameter_string(glow::VENDOR),
+            renderer: glow.get_parameter_string(glow::RENDERER),
+            version: glow.get_parameter_string(glow::VERSION),
+            shading_language_version: glow.get_parameter_string(glow::SHADING_LANGUAGE_VERSION),
+        }
+    }
+}
+impl std::fmt::Display for GlInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "gl vendor: {}\ngl renderer: {}\ngl version: {}\nglsl version: {}",
+            self.vendor, self.renderer, self.version, self.shading_language_version
+        )
+    }
+}
+
+// bounds of the requested display, None for the primary display or an index that doesn't exist
+fn display_bounds(
+    sdl_vss: &sdl2::VideoSubsystem,
+    display_index: Option<i32>,
+) -> Option<sdl2::rect::Rect> {
+    let index = display_index?;
+    let count = match sdl_vss.num_video_displays() {
+        Ok(x) => x,
+        Err(e) => {
+            log::warn!("couldn't count displays, using the primary display: {e}");
+            return None;
+        }
+    };
+    if index < 0 || index >= count {
+        log::warn!("display {index} doesn't exist ({count} found), using the primary display");
+        return None;
+    }
+    sdl_vss
+        .display_bounds(index)
+        .map_err(|e| {
+            log::warn!("couldn't get bounds of display {index}, using the primary display: {e}")
+        })
+        .ok()
+}
+
+fn is_event_for_window(event: &sdl2::event::Event, window_id: u32) -> bool {
+    match event.get_window_id() {
+        Some(id) => id == window_id,
+        None => true,
+    }
+}
+
+fn is_window_focused(sdl_wnd: &sdl2::video::Window) -> bool {
+    sdl_wnd.window_flags() & sdl2::sys::SDL_WindowFlags::SDL_WINDOW_INPUT_FOCUS as u32 != 0
+}
+
+struct GlDebugSettings {
+    level: GlDebugLevel,
+    synchronous: bool,
+}
+
+// most verbose severity forwarded to the log, each level includes the ones above it
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum GlDebugLevel {
+    Off,
+    High,
+    Medium,
+    Low,
+    Notification,
+}
+impl GlDebugLevel {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "off" => Some(Self::Off),
+            "high" => Some(Self::High),
+            "medium" => Some(Self::Medium),
+            "low" => Some(Self::Low),
+            "notification" => Some(Self::Notification),
+            _ => None,
+        }
+    }
+
+    fn forwards(self, severity: u32) -> bool {
+        let level = match severity {
+            glow::DEBUG_SEVERITY_HIGH => Self::High,
+            glow::DEBUG_SEVERITY_MEDIUM => Self::Medium,
+            glow::DEBUG_SEVERITY_LOW => Self::Low,
+            _ => Self::Notification,
+        };
+        self != Self::Off && level <= self
+    }
+}
+
 const DEBUG_CALLBACK_ID_BLOCKLIST: [u32; 1] = [
     0x20071,
 ];
@@ -36,16 +36,28 @@ impl ProfileSet {
         self.start = None;
     }
 
+    // the stats are zero when there aren't enough runs for them, e.g. a set that was skipped for
+    // the whole sample time
     pub fn mean(&self) -> Duration {
+        if self.runs.is_empty() {
+            return Duration::ZERO;
+        }
         let sum: Duration = self.runs.iter().sum();
         sum / self.runs.len() as u32
     }
 
     pub fn median(&self) -> Duration {
+        if self.runs.is_empty() {
+            return Duration::ZERO;
+        }
         self.runs[self.runs.len() / 2]
     }
 
     pub fn variance(&self) -> Duration {
+        // a single run has nothing to vary from
+        if self.runs.len() < 2 {
+            return Duration::ZERO;
+        }
         let mean = self.mean().as_micros() as i128;
         let top_term: i128 = self
             .runs
@@ -0,0 +1,79 @@
+// every shader baked into k9 lives in src/shaders and is included from here, the layouts the
+// rust side assumes are noted next to each source so changes to either can be checked together.
+// the attribute and uniform names the rust side uses are constants here, the tests check that
+// they're all in the sources.
+
+// attributes of graphics::Vertex as (location, name), shared by the texquad and line shaders.
+// pos is a vec3, uv a vec2.
+pub(crate) const VERTEX_POS: (u32, &str) = (0, "pos");
+pub(crate) const VERTEX_UV: (u32, &str) = (1, "uv");
+
+// texquad, vertices are graphics::Vertex.
+// uniforms: mat4 transform (vert), sampler2D tex (frag, slot 0).
+pub(crate) const TEXQUAD_VERT: &str = include_str!("shaders/k9_texquad.vert.glsl");
+pub(crate) const TEXQUAD_FRAG: &str = include_str!("shaders/k9_texquad.frag.glsl");
+pub(crate) const TEXQUAD_TRANSFORM: &str = "transform";
+
+// texquad cutout, a drop in frag for texquad that discards below 0.5 alpha. meant for foliage
+// style textures, pairs with alpha to coverage when msaa is on.
+// uniforms: sampler2D tex (frag, slot 0).
+pub(crate) const TEXQUAD_CUTOUT_FRAG: &str = include_str!("shaders/k9_texquad_cutout.frag.glsl");
+
+// line, flat coloured lines over graphics::Vertex, the uv attribute is unused. the pick pass
+// draws with it too, encoding pick ids in the colour.
+// uniforms: mat4 transform (vert), vec4 colour (frag).
+pub(crate) const LINE_VERT: &str = include_str!("shaders/k9_line.vert.glsl");
+pub(crate) const LINE_FRAG: &str = include_str!("shaders/k9_line.frag.glsl");
+pub(crate) const LINE_TRANSFORM: &str = "transform";
+pub(crate) const LINE_COLOUR: &str = "colour";
+
+// egui debug ui, vertices are EguiVertexPod: a vec2 pos, a vec2 uv and the srgba colour as 4
+// unnormalized u8s, the shader divides by 255.
+// uniforms: vec2 u_screen_size (vert), sampler2D u_sampler (frag, slot 0).
+pub(crate) const EGUI_DEBUG_UI_VERT: &str = include_str!("shaders/k9_egui_debug_ui.vert.glsl");
+pub(crate) const EGUI_DEBUG_UI_FRAG: &str = include_str!("shaders/k9_egui_debug_ui.frag.glsl");
+pub(crate) const EGUI_DEBUG_UI_POS: (u32, &str) = (0, "a_pos");
+pub(crate) const EGUI_DEBUG_UI_UV: (u32, &str) = (1, "a_tc");
+pub(crate) const EGUI_DEBUG_UI_COLOUR: (u32, &str) = (2, "a_srgba");
+pub(crate) const EGUI_DEBUG_UI_SCREEN_SIZE: &str = "u_screen_size";
+pub(crate) const EGUI_DEBUG_UI_SAMPLER: &str = "u_sampler";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn has_attribute(source: &str, (location, name): (u32, &str)) -> bool {
+        source.lines().any(|x| {
+            let x = x.trim();
+            x.starts_with(&format!("layout (location = {location}) in "))
+                && x.ends_with(&format!(" {name};"))
+        })
+    }
+
+    fn has_uniform(source: &str, name: &str) -> bool {
+        source.lines().any(|x| {
+            let x = x.trim();
+            x.starts_with("uniform ") && x.ends_with(&format!(" {name};"))
+        })
+    }
+
+    #[test]
+    fn attributes_are_in_sources() {
+        for source in [TEXQUAD_VERT, LINE_VERT] {
+            assert!(has_attribute(source, VERTEX_POS));
+            assert!(has_attribute(source, VERTEX_UV));
+        }
+        assert!(has_attribute(EGUI_DEBUG_UI_VERT, EGUI_DEBUG_UI_POS));
+        assert!(has_attribute(EGUI_DEBUG_UI_VERT, EGUI_DEBUG_UI_UV));
+        assert!(has_attribute(EGUI_DEBUG_UI_VERT, EGUI_DEBUG_UI_COLOUR));
+    }
+
+    #[test]
+    fn uniforms_are_in_sources() {
+        assert!(has_uniform(TEXQUAD_VERT, TEXQUAD_TRANSFORM));
+        assert!(has_uniform(LINE_VERT, LINE_TRANSFORM));
+        assert!(has_uniform(LINE_FRAG, LINE_COLOUR));
+        assert!(has_uniform(EGUI_DEBUG_UI_VERT, EGUI_DEBUG_UI_SCREEN_SIZE));
+        assert!(has_uniform(EGUI_DEBUG_UI_FRAG, EGUI_DEBUG_UI_SAMPLER));
+    }
+}
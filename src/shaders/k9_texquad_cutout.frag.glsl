@@ -0,0 +1,21 @@
+#version 330 core
+
+in vec2 uv_coord;
+out vec4 out_colour;
+
+uniform sampler2D tex;
+
+const float ALPHA_CUTOFF = 0.5;
+
+void main() {
+    vec4 colour = texture(tex, uv_coord);
+
+    // sharpens alpha to about a pixel wide ramp around the cutoff, with alpha to coverage this
+    // gives antialiased edges instead of a blurry fringe
+    float alpha = (colour.a - ALPHA_CUTOFF) / max(fwidth(colour.a), 0.0001) + 0.5;
+    if (alpha <= 0.0) {
+        discard;
+    }
+
+    out_colour = vec4(colour.rgb, clamp(alpha, 0.0, 1.0));
+}
\ No newline at end of file
@@ -6,6 +6,7 @@ use crate::{
     camera::ScreenCamera,
     debug_ui::{console::DebugUiWindow, ConsoleCommand},
     entity_component::EntityTable,
+    input::{KeyboardState, MouseState},
 };
 
 pub trait System: SystemCallbacks {
@@ -16,6 +17,9 @@ pub trait SystemCallbacks {
     fn first_call(&mut self, first_call_state: FirstCallState, frame_state: FrameState);
     fn update(&mut self, state: FrameState);
     fn exiting(&mut self, state: FrameState);
+    // draws egui each frame the debug ui is open, for transient debug views that don't need a
+    // registered window. ctx is the debug ui's, drawn over the console.
+    fn debug_draw(&mut self, _ctx: &egui::Context) {}
 }
 
 pub struct FrameState<'a> {
@@ -24,9 +28,103 @@ pub struct FrameState<'a> {
     pub screen_camera: &'a mut ScreenCamera,
     pub screen_dimensions: (u32, u32),
     pub screen_scale: f32,
+    pub window_id: u32,
+    pub window_focused: bool,
+    pub keyboard: &'a KeyboardState,
+    pub mouse: &'a mut MouseState,
+    pub raw: RawContext<'a>,
+    // the debug ui's last frame while it's open and CreationArgs::debug_ui_offscreen is set
+    pub debug_ui_texture: Option<glow::NativeTexture>,
+    // whether the debug ui is open over the game, e.g. to pause gameplay while the console is up
+    pub debug_ui_open: bool,
+    pub(crate) requests: &'a mut FrameRequests,
+    pub(crate) picked_entity: Option<Uuid>,
+}
+impl<'a> FrameState<'a> {
+    pub fn is_key_down(&self, keycode: sdl2::keyboard::Keycode) -> bool {
+        self.keyboard.is_key_down(keycode)
+    }
+
+    // true only on the frame the key went down, ignores key repeats
+    pub fn is_key_pressed(&self, keycode: sdl2::keyboard::Keycode) -> bool {
+        self.keyboard.is_key_pressed(keycode)
+    }
+
+    pub fn mouse_delta(&self) -> (i32, i32) {
+        self.mouse.delta()
+    }
+
+    pub fn set_relative_mouse_mode(&mut self, enabled: bool) {
+        self.mouse.set_relative_mode(enabled);
+    }
+
+    // opens the debug ui with the console focused, as if backquote was pressed
+    pub fn request_open_console(&mut self) {
+        self.requests.open_console = true;
+    }
+
+    // picks the entity drawn under the mouse, see picked_entity
+    pub fn request_pick(&mut self) {
+        self.requests.pick = Some(self.mouse.position());
+    }
+
+    // window coordinates, 0,0 is the top left
+    pub fn request_pick_at(&mut self, x: i32, y: i32) {
+        self.requests.pick = Some((x, y));
+    }
+
+    // entity hit by the most recently completed pick. picks go through the renderer, so the
+    // result shows up a couple of frames after it was requested.
+    pub fn picked_entity(&self) -> Option<Uuid> {
+        self.picked_entity
+    }
+}
+
+// things systems ask for during a frame, handled by process::run and the graphics system
+#[derive(Default)]
+pub(crate) struct FrameRequests {
+    pub open_console: bool,
+    pub pick: Option<(i32, i32)>,
 }
 
 pub struct FirstCallState<'a> {
     pub console_commands: &'a mut BTreeMap<String, ConsoleCommand>,
     pub debug_windows: &'a mut BTreeMap<String, Box<dyn DebugUiWindow>>,
 }
+
+// escape hatch for talking to sdl/gl directly, e.g. to drive a third-party library.
+// k9 caches gl state between frames, so anything bound, enabled or changed through
+// here (programs, buffers, vaos, blend/depth/stencil state, the viewport, the
+// framebuffer) must be restored before returning. the window must not be resized,
+// hidden or have its gl context made non-current either.
+#[derive(Clone, Copy)]
+pub struct RawContext<'a> {
+    glow: &'a glow::Context,
+    window: &'a sdl2::video::Window,
+    video: &'a sdl2::VideoSubsystem,
+}
+impl<'a> RawContext<'a> {
+    pub(crate) fn new(
+        glow: &'a glow::Context,
+        window: &'a sdl2::video::Window,
+        video: &'a sdl2::VideoSubsystem,
+    ) -> Self {
+        Self {
+            glow,
+            window,
+            video,
+        }
+    }
+
+    pub fn glow(&self) -> &'a glow::Context {
+        self.glow
+    }
+
+    pub fn sdl_window(&self) -> &'a sdl2::video::Window {
+        self.window
+    }
+
+    pub fn sdl_video(&self) -> &'a sdl2::VideoSubsystem {
+        self.video
+    }
+}